@@ -7,111 +7,616 @@
 
 #define SUCCESS 0
 
-#define ERROR 1
+#define DEFAULT_MAX_MESSAGE_LEN ((8 * 1024) * 1024)
+
+#define DEFAULT_MAX_FRAGMENT_COUNT 32768
+
+#define DEFAULT_MAX_MIXED_PARTS 32768
+
+#define DEFAULT_MAX_PENDING 16
+
+#define MAX_QUIET_ZONE 32
+
+#define MAX_MODULE_SIZE 64
+
+#define MAX_FRAME_SIZE 8192
+
+#define FORMAT_GIF 0
+
+#define FORMAT_APNG 1
+
+#define MAX_FRAMES 1000
+
+#define MAX_SIZE 4096
+
+/**
+ * Bounds `frames * size * size`, so long animations have to use smaller frames.
+ */
+#define MAX_TOTAL_PIXELS ((256 * 1024) * 1024)
+
+#define DEFAULT_MAX_FRAGMENT_LEN 400
+
+#define DEFAULT_MIN_FRAGMENT_LEN 10
+
+#define MAX_FRAGMENT_LEN 2048
+
+#define MAX_FIRST_SEQ_NUM 100000
+
+#define EIP2930_TX_TYPE 1
+
+#define EIP1559_TX_TYPE 2
 
 typedef char *PtrString;
 
 typedef void *PtrVoid;
 
+/**
+ * A byte buffer allocated on the Rust side, `data` is null when `len` is `0`.
+ */
+typedef struct Bytes {
+  uint8_t *data;
+  uintptr_t len;
+} Bytes;
+
 typedef union Value {
-    PtrVoid _object;
-    bool _boolean;
-    uint32_t _uint32;
-    PtrString _string;
-    PtrVoid _null;
+  PtrVoid _object;
+  bool _boolean;
+  uint32_t _uint32;
+  PtrString _string;
+  struct Bytes _bytes;
+  PtrVoid _null;
 } Value;
 
+/**
+ * Every exported function returns a heap allocated `Response`. The host owns it and must
+ * release it with `response_free` once it has read the value.
+ *
+ * * `success_string`: the string in `value` belongs to the response and is released with it,
+ *   copy it before calling `response_free`.
+ * * `success_bytes`: likewise the `Bytes` buffer in `value` is released with the response, copy
+ *   it before calling `response_free`.
+ * * `success_object`: `value` holds an object handle and `value_type` names its registry type
+ *   (e.g. `eth-signature`). The object outlives the response, release it with its `*_free`
+ *   function or `utils_free`.
+ * * `error`: `status_code` carries the `ErrorKind` and `error_message` its JSON description,
+ *   which belongs to the response and is released with it.
+ */
 typedef struct Response {
-    uint32_t status_code;
-    PtrString error_message;
-    PtrString value_type;
-    union Value value;
+  uint32_t status_code;
+  PtrString error_message;
+  PtrString value_type;
+  union Value value;
 } Response;
 
 typedef struct Response *PtrResponse;
 
-PtrResponse crypto_hd_key_get_key_data(void *crypto_hdkey);
-
-PtrResponse crypto_hd_key_get_uncompressed_key_data(void *compressed_key);
+typedef const uint8_t *PtrBytes;
 
-PtrResponse crypto_hd_key_get_chain_code(void *crypto_hdkey);
+PtrResponse crypto_hd_key_free(PtrVoid crypto_hdkey);
 
-PtrResponse crypto_hd_key_get_name(void *crypto_hdkey);
+PtrResponse crypto_hd_key_get_key_data(PtrVoid crypto_hdkey);
 
-PtrResponse crypto_hd_key_get_path(void *crypto_hdkey);
+PtrResponse crypto_hd_key_get_uncompressed_key_data(PtrString compressed_key);
 
-PtrResponse crypto_hd_key_get_source_fingerprint(void *crypto_hdkey);
+PtrResponse crypto_hd_key_get_chain_code(PtrVoid crypto_hdkey);
 
-PtrResponse crypto_hd_key_get_account_index(void *crypto_hdkey, uint32_t level);
+PtrResponse crypto_hd_key_get_name(PtrVoid crypto_hdkey);
 
-PtrResponse crypto_hd_key_get_depth(void *crypto_hdkey);
+PtrResponse crypto_hd_key_get_path(PtrVoid crypto_hdkey);
 
-PtrResponse crypto_hd_key_get_note(void *crypto_hdkey);
+PtrResponse crypto_hd_key_get_children_path(PtrVoid crypto_hdkey);
 
-PtrResponse crypto_account_get_accounts_len(void *crypto_account);
+PtrResponse crypto_hd_key_get_source_fingerprint(PtrVoid crypto_hdkey);
 
-PtrResponse crypto_account_get_account(void *crypto_account, uint32_t index);
+PtrResponse crypto_hd_key_get_account_index(PtrVoid crypto_hdkey, uint32_t level);
 
-PtrResponse crypto_account_get_master_fingerprint(void *crypto_account);
+PtrResponse crypto_hd_key_get_depth(PtrVoid crypto_hdkey);
 
-PtrResponse crypto_output_get_hd_key(void *crypto_output);
+PtrResponse crypto_hd_key_get_note(PtrVoid crypto_hdkey);
 
-PtrResponse solana_crypto_multi_accounts_get_master_fingerprint(void *crypto_multi_accounts);
+PtrResponse crypto_hd_key_get_bip32_xpub(PtrVoid crypto_hdkey);
 
-PtrResponse solana_crypto_multi_accounts_get_device(void *crypto_multi_accounts);
-
-PtrResponse solana_crypto_multi_accounts_get_keys_len(void *crypto_multi_accounts);
-
-PtrResponse solana_crypto_multi_accounts_get_key(void *crypto_multi_accounts,
-                                                 uint32_t index);
+void response_free(PtrResponse response);
 
 PtrResponse solana_sign_request_new(void);
 
-PtrResponse solana_sign_request_construct(void *request_id,
-                                          void *sign_data,
-                                          void *path,
+PtrResponse solana_sign_request_free(PtrVoid sol_sign_request);
+
+PtrResponse solana_sign_request_construct(PtrString request_id,
+                                          PtrString sign_data,
+                                          PtrString path,
                                           uint32_t xfp,
-                                          void *address,
-                                          void *origin,
+                                          PtrString address,
+                                          PtrString origin,
                                           uint32_t sign_type);
 
-PtrResponse solana_sign_request_get_ur_encoder(void *sol_sign_request);
+PtrResponse solana_sign_request_construct_from_bytes(PtrString request_id,
+                                                     PtrBytes sign_data,
+                                                     uintptr_t sign_data_len,
+                                                     PtrString path,
+                                                     uint32_t xfp,
+                                                     PtrString address,
+                                                     PtrString origin,
+                                                     uint32_t sign_type);
+
+PtrResponse solana_sign_request_get_ur_encoder(PtrVoid sol_sign_request);
+
+PtrResponse solana_sign_request_get_ur_encoder_with_options(PtrVoid sol_sign_request,
+                                                            uint32_t max_fragment_len,
+                                                            uint32_t min_fragment_len,
+                                                            uint32_t first_seq_num);
+
+PtrResponse solana_sign_request_get_request_id(PtrVoid sol_sign_request);
 
-PtrResponse solana_sign_request_get_request_id(void *sol_sign_request);
+PtrResponse solana_signature_free(PtrVoid solana_signature);
 
-PtrResponse solana_signature_get_signature(void *solana_signarure);
+PtrResponse solana_signature_get_signature(PtrVoid solana_signarure);
 
-PtrResponse solana_signature_get_request_id(void *solana_signature);
+PtrResponse solana_signature_get_request_id(PtrVoid solana_signature);
+
+PtrResponse extend_crypto_multi_accounts_free(PtrVoid crypto_multi_accounts);
+
+PtrResponse extend_crypto_multi_accounts_get_master_fingerprint(PtrVoid crypto_multi_accounts);
+
+PtrResponse extend_crypto_multi_accounts_get_device(PtrVoid crypto_multi_accounts);
+
+PtrResponse extend_crypto_multi_accounts_get_keys_len(PtrVoid crypto_multi_accounts);
+
+PtrResponse extend_crypto_multi_accounts_get_key(PtrVoid crypto_multi_accounts, uint32_t index);
+
+/**
+ * Decodes a single-part UR without a decoder, returning `{"type": ..., "cbor": <hex>}`.
+ */
+PtrResponse ur_decode_single(PtrString ur);
+
+PtrResponse ur_decoder_new(void);
+
+/**
+ * Creates a decoder that rejects parts of messages longer than `max_message_len` bytes or split
+ * into more than `max_fragment_count` fragments, and stops accepting new mixed parts once
+ * `max_mixed_parts` are held. The parts kept for `ur_decoder_serialize` are bounded to eight
+ * times `max_message_len` bytes. `0` selects the default for that limit.
+ */
+PtrResponse ur_decoder_new_with_limits(uint32_t max_message_len,
+                                       uint32_t max_fragment_count,
+                                       uint32_t max_mixed_parts);
+
+PtrResponse ur_decoder_free(PtrVoid decoder);
+
+PtrResponse ur_decoder_receive(PtrVoid decoder, PtrString ur);
+
+/**
+ * Scans a grayscale camera frame for QR codes and feeds each one into the decoder. Returns
+ * `{"found": 2, "received": 1, "rejected": [<error>], "complete": false}` where `rejected` holds
+ * one error object per code that was not accepted, e.g. a QR code that is not a UR.
+ */
+PtrResponse ur_decoder_receive_frame(PtrVoid decoder,
+                                     uint32_t width,
+                                     uint32_t height,
+                                     uint32_t stride,
+                                     PtrBytes luma,
+                                     uintptr_t luma_len);
+
+/**
+ * Drops every part received so far, so the decoder can be reused for another message. The
+ * limits it was created with are kept.
+ */
+PtrResponse ur_decoder_reset(PtrVoid decoder);
+
+/**
+ * Saves the progress of the decoder as a JSON string, so a scan interrupted by the host being
+ * killed can be resumed with `ur_decoder_deserialize`.
+ */
+PtrResponse ur_decoder_serialize(PtrVoid decoder);
+
+/**
+ * Restores a decoder saved with `ur_decoder_serialize`. Its limits are rebuilt from the state
+ * and capped at the defaults.
+ */
+PtrResponse ur_decoder_deserialize(PtrString state);
+
+PtrResponse ur_decoder_is_complete(PtrVoid decoder);
+
+PtrResponse ur_decoder_expected_part_count(PtrVoid decoder);
+
+PtrResponse ur_decoder_processed_parts_count(PtrVoid decoder);
+
+PtrResponse ur_decoder_received_parts_count(PtrVoid decoder);
+
+/**
+ * Estimated progress of the scan in percent, `100` once the message is complete.
+ */
+PtrResponse ur_decoder_estimated_percent_complete(PtrVoid decoder);
+
+PtrResponse ur_decoder_get_type(PtrVoid decoder);
+
+PtrResponse ur_decoder_result(PtrVoid decoder);
+
+PtrResponse ur_decoder_result_bytes(PtrVoid decoder);
+
+/**
+ * `target_type` is either the registry type of the result object or the UR type it is encoded
+ * with, which only differ for `cardano-catalyst-signature`.
+ */
+PtrResponse ur_decoder_resolve(PtrVoid decoder, PtrString target_type);
+
+PtrResponse ur_decoder_resolve_auto(PtrVoid decoder);
+
+/**
+ * Creates a session that drops incomplete messages after `idle_timeout_ms` without a new part,
+ * `0` keeps them until the session is reset or they are evicted for a new message.
+ */
+PtrResponse ur_decoder_session_new(uint32_t idle_timeout_ms);
+
+/**
+ * Like `ur_decoder_session_new`, scanning at most `max_pending` messages at once and decoding
+ * each one with the limits of `ur_decoder_new_with_limits`. `0` selects the default for that
+ * limit.
+ */
+PtrResponse ur_decoder_session_new_with_limits(uint32_t idle_timeout_ms,
+                                               uint32_t max_pending,
+                                               uint32_t max_message_len,
+                                               uint32_t max_fragment_count,
+                                               uint32_t max_mixed_parts);
+
+PtrResponse ur_decoder_session_free(PtrVoid session);
+
+/**
+ * Returns `{"type": "crypto-psbt", "complete": true, "expired": 0}`, where `complete` is only
+ * set by the part that completed its message and `expired` counts the incomplete messages
+ * dropped for being idle or to make room for a new one. Parts of new messages fail with
+ * `LimitExceeded` while too many completed messages wait for `ur_decoder_session_take_completed`.
+ */
+PtrResponse ur_decoder_session_receive(PtrVoid session, PtrString ur);
+
+/**
+ * Lists the messages still being scanned, most recently seen first.
+ */
+PtrResponse ur_decoder_session_pending(PtrVoid session);
+
+PtrResponse ur_decoder_session_completed_count(PtrVoid session);
+
+/**
+ * Hands the oldest completed message over as a `ur-decoder`, to be used with `ur_decoder_resolve`
+ * and the other result getters and released with `ur_decoder_free`.
+ */
+PtrResponse ur_decoder_session_take_completed(PtrVoid session);
+
+/**
+ * Drops every pending and completed message, keeping the idle timeout and limits.
+ */
+PtrResponse ur_decoder_session_reset(PtrVoid session);
+
+/**
+ * Describes a UR string without decoding it, e.g.
+ * `{"scheme": "ur", "type": "crypto-psbt", "multipart": true, "seq": 1, "seqLen": 3, "checksumValid": true}`.
+ * `seq` and `seqLen` are `1` for a single-part UR. Strings that are not URs at all are reported
+ * as `InvalidScheme` errors and malformed bytewords as `InvalidBytewords` errors, while
+ * `checksumValid` only reflects whether the trailing CRC32 matches the payload.
+ */
+PtrResponse ur_parse(PtrString ur);
+
+/**
+ * Returns `{"width": 29, "modules": "0101..."}` where `modules` is the hex of one byte per module
+ * (`01` dark, `00` light) for a row-major square of `width * width` modules.
+ */
+PtrResponse qr_encode_matrix(PtrString text, uint32_t ec_level, uint32_t quiet_zone);
+
+PtrResponse qr_encode_svg(PtrString text,
+                          uint32_t ec_level,
+                          uint32_t quiet_zone,
+                          uint32_t module_size);
+
+PtrResponse qr_encode_png(PtrString text,
+                          uint32_t ec_level,
+                          uint32_t quiet_zone,
+                          uint32_t module_size);
+
+/**
+ * Renders the upcoming parts of an encoder as an animated GIF (`format` 0) or APNG (`format` 1)
+ * of `qr_size` pixels square. The encoder advances past every part that was rendered and is left
+ * as it was when the export fails. Frames are limited to `MAX_TOTAL_PIXELS` in total, larger
+ * requests fail with `LimitExceeded`. GIF delays are in hundredths of a second, so
+ * `frame_interval_ms` is rounded to the nearest 10 ms there.
+ */
+PtrResponse ur_encoder_export_animation(PtrVoid ur_encoder,
+                                        uint32_t format,
+                                        uint32_t frame_count,
+                                        uint32_t extra_fountain_parts,
+                                        uint32_t frame_interval_ms,
+                                        uint32_t qr_size);
+
+void utils_free(PtrVoid any_ptr);
+
+/**
+ * Wraps CBOR that has already been serialized by the host, so that UR types without a
+ * dedicated wrapper can be encoded as well.
+ */
+PtrResponse ur_encoder_new(PtrString ur_type,
+                           PtrBytes cbor,
+                           uintptr_t cbor_len,
+                           uint32_t max_fragment_len);
+
+/**
+ * Encodes a whole message as a single-part `ur:<type>/<payload>` string, without an encoder.
+ */
+PtrResponse ur_encode_single(PtrString ur_type, PtrBytes cbor, uintptr_t cbor_len);
+
+PtrResponse ur_encoder_free(PtrVoid ur_encoder);
+
+PtrResponse ur_encoder_next_part(PtrVoid ur_encoder);
+
+PtrResponse ur_encoder_fragment_count(PtrVoid ur_encoder);
+
+PtrResponse ur_encoder_current_sequence(PtrVoid ur_encoder);
+
+PtrResponse ur_encoder_is_single_part(PtrVoid ur_encoder);
+
+/**
+ * In pure-sequence mode the encoder cycles through the first `fragment_count` parts, which
+ * carry one fragment each, and never emits fountain mixed parts.
+ */
+PtrResponse ur_encoder_set_pure_sequence(PtrVoid ur_encoder, bool pure_sequence);
+
+/**
+ * Validates `typed_data`, the JSON of an `eth_signTypedData_v4` request, and hashes it as
+ * `{"domainSeparator": <hex>, "messageHash": <hex>, "digest": <hex>}`, `messageHash` being
+ * `null` when the primary type is `EIP712Domain`.
+ */
+PtrResponse eth_typed_data_hash(PtrString typed_data);
+
+/**
+ * Registers the functions of a JSON ABI for `eth_abi_decode_calldata` and the transaction
+ * summaries, returning how many functions were registered. ABIs with a function that has the
+ * selector of a token standard function are rejected, see `register_abi`.
+ */
+PtrResponse eth_abi_register(PtrString abi);
+
+/**
+ * See `decode_calldata`, `data` is the hex calldata including the selector.
+ */
+PtrResponse eth_abi_decode_calldata(PtrString data);
 
 PtrResponse eth_sign_request_new(void);
 
-PtrResponse eth_sign_request_construct(void *request_id,
-                                       void *sign_data,
-                                       uint32_t data_type,
+PtrResponse eth_sign_request_free(PtrVoid eth_sign_request);
+
+PtrResponse eth_sign_request_construct(PtrString request_id,
+                                       PtrString sign_data,
+                                       uint32_t sign_type,
                                        uint32_t chain_id,
-                                       void *path,
+                                       PtrString path,
                                        uint32_t xfp,
-                                       void *address,
-                                       void *origin);
+                                       PtrString address,
+                                       PtrString origin);
 
-PtrResponse eth_sign_request_get_ur_encoder(void *eth_sign_request);
+PtrResponse eth_sign_request_construct_from_bytes(PtrString request_id,
+                                                  PtrBytes sign_data,
+                                                  uintptr_t sign_data_len,
+                                                  uint32_t sign_type,
+                                                  uint32_t chain_id,
+                                                  PtrString path,
+                                                  uint32_t xfp,
+                                                  PtrString address,
+                                                  PtrString origin);
 
-PtrResponse eth_sign_request_get_request_id(void *eth_sign_request);
+PtrResponse eth_sign_request_get_ur_encoder(PtrVoid eth_sign_request);
 
-PtrResponse eth_signature_get_signature(void *eth_signarure);
+PtrResponse eth_sign_request_get_ur_encoder_with_options(PtrVoid eth_sign_request,
+                                                         uint32_t max_fragment_len,
+                                                         uint32_t min_fragment_len,
+                                                         uint32_t first_seq_num);
 
-PtrResponse eth_signature_get_request_id(void *eth_signature);
+PtrResponse eth_sign_request_get_request_id(PtrVoid eth_sign_request);
 
-PtrResponse ur_decoder_new(void);
+PtrResponse eth_sign_request_get_sign_data(PtrVoid eth_sign_request);
+
+PtrResponse eth_sign_request_get_sign_data_bytes(PtrVoid eth_sign_request);
+
+/**
+ * Same values as the `sign_type` accepted by `eth_sign_request_construct`: `1` legacy
+ * transaction, `2` EIP-712 typed data, `3` personal message, `4` typed transaction.
+ */
+PtrResponse eth_sign_request_get_data_type(PtrVoid eth_sign_request);
+
+/**
+ * The chain id as a decimal string, since a decoded request may carry one that does not fit
+ * in 32 bits.
+ */
+PtrResponse eth_sign_request_get_chain_id(PtrVoid eth_sign_request);
+
+PtrResponse eth_sign_request_get_derivation_path(PtrVoid eth_sign_request);
+
+PtrResponse eth_sign_request_get_source_fingerprint(PtrVoid eth_sign_request);
+
+PtrResponse eth_sign_request_get_address(PtrVoid eth_sign_request);
+
+PtrResponse eth_sign_request_get_origin(PtrVoid eth_sign_request);
+
+PtrResponse eth_signature_free(PtrVoid eth_signature);
+
+PtrResponse eth_signature_get_signature(PtrVoid eth_signature);
+
+PtrResponse eth_signature_get_signature_bytes(PtrVoid eth_signature);
+
+PtrResponse eth_signature_get_request_id(PtrVoid eth_signature);
+
+/**
+ * Recovers who signed `eth_signature` for `eth_sign_request` and checks it against the address
+ * the request was made for, returning
+ * `{"hash": <hex>, "publicKey": <hex>, "address": <hex>, "addressMatches": true}`.
+ * `addressMatches` is `null` when the request does not carry an address. An EIP-155 `v` has to
+ * commit to the chain id of the request.
+ */
+PtrResponse eth_signature_recover_signer(PtrVoid eth_signature, PtrVoid eth_sign_request);
+
+/**
+ * Describes what the request asks to sign, as a JSON object whose `dataType` is one of
+ * `transaction`, `personalMessage` or `typedData`:
+ *
+ * * transactions list their RLP fields under their usual names, e.g. `nonce`, `maxFeePerGas`
+ *   or `accessList`, along with a `transactionType` of `legacy`, `eip2930` or `eip1559` and the
+ *   decoded `call`, if any
+ * * personal messages carry `hex` and, when the message is valid UTF-8, `utf8`
+ * * typed data carries its `primaryType` and `domain` and `message` trees as built by
+ *   `TypedData::describe`
+ */
+PtrResponse eth_sign_request_get_summary(PtrVoid eth_sign_request);
+
+/**
+ * Merges `eth_signature` into the transaction of `eth_sign_request`, returning
+ * `{"rawTransaction": <hex>, "hash": <hex>}` ready to be broadcast.
+ */
+PtrResponse eth_signature_build_signed_transaction(PtrVoid eth_signature, PtrVoid eth_sign_request);
+
+PtrResponse cardano_sign_request_new(void);
+
+PtrResponse cardano_sign_request_free(PtrVoid cardano_sign_request);
+
+PtrResponse cardano_sign_request_construct(PtrString request_id,
+                                           PtrString sign_data,
+                                           PtrString utxos,
+                                           PtrString cert_keys,
+                                           PtrString origin);
+
+PtrResponse cardano_sign_request_construct_from_bytes(PtrString request_id,
+                                                      PtrBytes sign_data,
+                                                      uintptr_t sign_data_len,
+                                                      PtrString utxos,
+                                                      PtrString cert_keys,
+                                                      PtrString origin);
+
+PtrResponse cardano_sign_request_get_ur_encoder(PtrVoid cardano_sign_request);
+
+PtrResponse cardano_sign_request_get_ur_encoder_with_options(PtrVoid cardano_sign_request,
+                                                             uint32_t max_fragment_len,
+                                                             uint32_t min_fragment_len,
+                                                             uint32_t first_seq_num);
+
+PtrResponse cardano_sign_request_get_request_id(PtrVoid cardano_sign_request);
+
+PtrResponse cardano_signature_free(PtrVoid cardano_signature);
+
+PtrResponse cardano_signature_get_witness_set(PtrVoid cardano_signature);
+
+PtrResponse cardano_signature_get_witness_set_bytes(PtrVoid cardano_signature);
+
+PtrResponse cardano_signature_get_request_id(PtrVoid cardano_signature);
+
+PtrResponse cardano_sign_cip8_data_request_new(void);
+
+PtrResponse cardano_sign_cip8_data_request_free(PtrVoid request);
+
+PtrResponse cardano_sign_cip8_data_request_construct(PtrString request_id,
+                                                     PtrString mfp,
+                                                     PtrString sign_data,
+                                                     PtrString derivation_path,
+                                                     PtrString xpub,
+                                                     PtrString origin,
+                                                     bool hash_payload,
+                                                     PtrString address_bench32,
+                                                     uint32_t address_type);
+
+PtrResponse cardano_sign_cip8_data_signature_new(void);
+
+PtrResponse cardano_sign_cip8_data_signature_free(PtrVoid signature);
+
+PtrResponse cardano_sign_cip8_data_signature_construct(PtrString request_id,
+                                                       PtrString signature,
+                                                       PtrString public_key,
+                                                       PtrString address_field);
+
+PtrResponse cardano_sign_cip8_data_signature_get_request_id(PtrVoid signature);
+
+PtrResponse cardano_sign_cip8_data_signature_get_signature(PtrVoid signature);
+
+PtrResponse cardano_sign_cip8_data_signature_get_public_key(PtrVoid signature);
+
+PtrResponse cardano_sign_cip8_data_signature_get_address_field(PtrVoid signature);
+
+PtrResponse cardano_sign_data_request_new(void);
+
+PtrResponse cardano_sign_data_request_free(PtrVoid request);
+
+PtrResponse cardano_sign_data_request_construct(PtrString request_id,
+                                                PtrString mfp,
+                                                PtrString sign_data,
+                                                PtrString derivation_path,
+                                                PtrString origin,
+                                                PtrString xpub);
+
+PtrResponse cardano_sign_data_signature_new(void);
+
+PtrResponse cardano_sign_data_signature_free(PtrVoid signature);
+
+PtrResponse cardano_sign_data_signature_construct(PtrString request_id,
+                                                  PtrString signature,
+                                                  PtrString public_key);
+
+PtrResponse cardano_sign_data_signature_get_request_id(PtrVoid signature);
+
+PtrResponse cardano_sign_data_signature_get_signature(PtrVoid signature);
+
+PtrResponse cardano_sign_data_signature_get_public_key(PtrVoid signature);
+
+PtrResponse cardano_catalyst_signature_new(void);
+
+PtrResponse cardano_catalyst_signature_free(PtrVoid catalyst_signature);
+
+PtrResponse cardano_catalyst_signature_construct(PtrString request_id, PtrString signature);
+
+PtrResponse cardano_catalyst_signature_get_request_id(PtrVoid catalyst_signature);
+
+PtrResponse cardano_catalyst_signature_get_signature(PtrVoid catalyst_signature);
+
+PtrResponse cardano_catalyst_voting_registration_new(void);
+
+PtrResponse cardano_catalyst_voting_registration_free(PtrVoid request);
+
+PtrResponse cardano_catalyst_voting_registration_construct(PtrString request_id,
+                                                           PtrString mfp,
+                                                           PtrString delegations,
+                                                           PtrString stake_pub,
+                                                           PtrString payment_address,
+                                                           PtrString nonce,
+                                                           uint8_t voting_purpose,
+                                                           PtrString derivation_path,
+                                                           PtrString origin,
+                                                           uint8_t sign_type);
+
+PtrResponse cardano_sign_tx_hash_request_free(PtrVoid request);
+
+PtrResponse cardano_sign_tx_hash_request_construct(PtrString request_id,
+                                                   PtrString tx_hash,
+                                                   PtrString paths,
+                                                   PtrString origin,
+                                                   PtrString address_list);
+
+PtrResponse crypto_output_free(PtrVoid crypto_output);
+
+PtrResponse crypto_output_get_hd_key(PtrVoid crypto_output);
+
+PtrResponse crypto_account_free(PtrVoid crypto_account);
+
+PtrResponse crypto_account_get_accounts_len(PtrVoid crypto_account);
+
+PtrResponse crypto_account_get_account(PtrVoid crypto_account, uint32_t index);
+
+PtrResponse crypto_account_get_master_fingerprint(PtrVoid crypto_account);
+
+PtrResponse crypto_psbt_free(PtrVoid crypto_psbt);
 
-PtrResponse ur_decoder_receive(void *decoder, void *ur);
+PtrResponse crypto_psbt_get_data(PtrVoid crypto_psbt);
 
-PtrResponse ur_decoder_is_complete(void *decoder);
+PtrResponse crypto_psbt_get_data_bytes(PtrVoid crypto_psbt);
 
-PtrResponse ur_decoder_result(void *decoder);
+PtrResponse crypto_psbt_construct(PtrString data);
 
-PtrResponse ur_decoder_resolve(void *decoder, void *target_type);
+PtrResponse crypto_psbt_construct_from_bytes(PtrBytes data, uintptr_t len);
 
-void utils_free(void *any_ptr);
+PtrResponse crypto_psbt_get_ur_encoder(PtrVoid crypto_psbt);
 
-PtrResponse ur_encoder_next_part(void *ur_encoder);
+PtrResponse crypto_psbt_get_ur_encoder_with_options(PtrVoid crypto_psbt,
+                                                    uint32_t max_fragment_len,
+                                                    uint32_t min_fragment_len,
+                                                    uint32_t first_seq_num);
@@ -16,6 +16,7 @@ public class SwiftUrRegistryFlutterPlugin: NSObject, FlutterPlugin {
     //write this function to enforce compiling these functions.
     //these functions are not actually called here.
     let anyPointer = UnsafeMutableRawPointer.allocate(byteCount: 4, alignment: 1);
+    let anyString = anyPointer.assumingMemoryBound(to: CChar.self);
 
     crypto_hd_key_get_key_data(anyPointer);
     crypto_hd_key_get_name(anyPointer);
@@ -31,18 +32,18 @@ public class SwiftUrRegistryFlutterPlugin: NSObject, FlutterPlugin {
     crypto_output_get_hd_key(anyPointer);
 
     crypto_psbt_get_data(anyPointer);
-    crypto_psbt_construct(anyPointer);
+    crypto_psbt_construct(anyString);
     crypto_psbt_get_ur_encoder(anyPointer);
 
     solana_sign_request_new();
-    solana_sign_request_construct(anyPointer, anyPointer, anyPointer, 1, anyPointer, anyPointer, 1);
+    solana_sign_request_construct(anyString, anyString, anyString, 1, anyString, anyString, 1);
     solana_sign_request_get_ur_encoder(anyPointer);
 
     solana_signature_get_signature(anyPointer);
     solana_signature_get_request_id(anyPointer);
 
     eth_sign_request_new();
-    eth_sign_request_construct(anyPointer, anyPointer, 0, 0, anyPointer, 0, anyPointer, anyPointer);
+    eth_sign_request_construct(anyString, anyString, 0, 0, anyString, 0, anyString, anyString);
     eth_sign_request_get_ur_encoder(anyPointer);
     eth_sign_request_get_request_id(anyPointer);
 
@@ -55,13 +56,15 @@ public class SwiftUrRegistryFlutterPlugin: NSObject, FlutterPlugin {
     extend_crypto_multi_accounts_get_key(anyPointer, 0);
 
     ur_decoder_new();
-    ur_decoder_receive(anyPointer, anyPointer);
+    ur_decoder_receive(anyPointer, anyString);
     ur_decoder_is_complete(anyPointer);
     ur_decoder_result(anyPointer)
-    ur_decoder_resolve(anyPointer, anyPointer)
+    ur_decoder_resolve(anyPointer, anyString)
 
     utils_free(anyPointer)
 
     ur_encoder_next_part(anyPointer)
+
+    response_free(nil)
   }
 }
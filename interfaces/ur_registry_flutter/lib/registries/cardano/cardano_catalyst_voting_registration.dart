@@ -59,7 +59,7 @@ class CardanoCatalystVotingRegistration extends NativeObject {
 
   CardanoCatalystVotingRegistration(Pointer<Void> object) : super() {
     nativeObject = object;
-    final response = nativeGetRequestId(nativeObject).ref;
+    final response = nativeGetRequestId(nativeObject);
     final uuidBuffer = response.getString();
     uuid = Uuid.unparse(hex.decode(uuidBuffer));
   }
@@ -99,13 +99,13 @@ class CardanoCatalystVotingRegistration extends NativeObject {
       derivationPath.toNativeUtf8(),
       origin.toNativeUtf8(),
       signType,
-    ).ref;
+    );
 
     nativeObject = response.getObject();
   }
 
   UREncoder toUREncoder() {
-    final response = nativeGetUREncoder(nativeObject).ref;
+    final response = nativeGetUREncoder(nativeObject);
     return UREncoder(response.getObject());
   }
 
@@ -56,7 +56,7 @@ class CardanoSignCip8DataRequest extends NativeObject {
 
   CardanoSignCip8DataRequest(Pointer<Void> object) : super() {
     nativeObject = object;
-    final response = nativeGetRequestId(nativeObject).ref;
+    final response = nativeGetRequestId(nativeObject);
     final uuidBuffer = response.getString();
     uuid = Uuid.unparse(hex.decode(uuidBuffer));
   }
@@ -94,13 +94,13 @@ class CardanoSignCip8DataRequest extends NativeObject {
       hashPayload,
       addressBench32.toNativeUtf8(),
       addressType,
-    ).ref;
+    );
 
     nativeObject = response.getObject();
   }
 
   UREncoder toUREncoder() {
-    final response = nativeGetUREncoder(nativeObject).ref;
+    final response = nativeGetUREncoder(nativeObject);
     return UREncoder(response.getObject());
   }
 
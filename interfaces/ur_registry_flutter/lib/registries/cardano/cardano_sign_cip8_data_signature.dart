@@ -34,25 +34,25 @@ class CardanoSignCip8DataSignature extends NativeObject {
       .asFunction();
 
   String getRequestId() {
-    final response = nativeGetRequestId(nativeObject).ref;
+    final response = nativeGetRequestId(nativeObject);
     final String requestIdBufferStr = response.getString();
     return Uuid.unparse(hex.decode(requestIdBufferStr));
   }
 
   List<int> getSignature() {
-    final response = nativeGetSignature(nativeObject).ref;
+    final response = nativeGetSignature(nativeObject);
     final String signature = response.getString();
     return hex.decode(signature);
   }
 
   List<int> getPublicKey() {
-    final response = nativeGetPublicKey(nativeObject).ref;
+    final response = nativeGetPublicKey(nativeObject);
     final String publicKey = response.getString();
     return hex.decode(publicKey);
   }
 
   List<int> getAddressField() {
-    final response = nativeGetAddressField(nativeObject).ref;
+    final response = nativeGetAddressField(nativeObject);
     final String addressField = response.getString();
     return hex.decode(addressField);
   }
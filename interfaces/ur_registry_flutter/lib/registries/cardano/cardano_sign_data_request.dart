@@ -39,7 +39,7 @@ class CardanoSignDataRequest extends NativeObject {
 
   CardanoSignDataRequest(Pointer<Void> object) : super() {
     nativeObject = object;
-    final response = nativeGetRequestId(nativeObject).ref;
+    final response = nativeGetRequestId(nativeObject);
     final uuidBuffer = response.getString();
     uuid = Uuid.unparse(hex.decode(uuidBuffer));
   }
@@ -62,7 +62,7 @@ class CardanoSignDataRequest extends NativeObject {
       derivationPath.toNativeUtf8(),
       origin.toNativeUtf8(),
       xpub.toNativeUtf8(),
-    ).ref;
+    );
 
     nativeObject = response.getObject();
   }
@@ -46,7 +46,7 @@ class CardanoSignRequest extends NativeObject {
 
   CardanoSignRequest(Pointer<Void> object) : super() {
     nativeObject = object;
-    final response = nativeGetRequestId(nativeObject).ref;
+    final response = nativeGetRequestId(nativeObject);
     final uuidBuffer = response.getString();
     uuid = Uuid.unparse(hex.decode(uuidBuffer));
   }
@@ -68,13 +68,13 @@ class CardanoSignRequest extends NativeObject {
       utxos.toNativeUtf8(),
       certKeys.toNativeUtf8(),
       origin.toNativeUtf8(),
-    ).ref;
+    );
 
     nativeObject = response.getObject();
   }
 
   UREncoder toUREncoder() {
-    final response = nativeGetUREncoder(nativeObject).ref;
+    final response = nativeGetUREncoder(nativeObject);
     return UREncoder(response.getObject());
   }
 
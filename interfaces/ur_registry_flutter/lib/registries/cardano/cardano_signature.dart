@@ -24,13 +24,13 @@ class CardanoSignature extends NativeObject {
       .asFunction();
 
   String getRequestId() {
-    final response = nativeGetRequestId(nativeObject).ref;
+    final response = nativeGetRequestId(nativeObject);
     final String requestIdBufferStr = response.getString();
     return Uuid.unparse(hex.decode(requestIdBufferStr));
   }
 
   List<int> getWitnessSet() {
-    final response = nativeGetWitnessSet(nativeObject).ref;
+    final response = nativeGetWitnessSet(nativeObject);
     final String witnessSet = response.getString();
     return hex.decode(witnessSet);
   }
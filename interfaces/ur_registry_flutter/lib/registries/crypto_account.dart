@@ -34,17 +34,15 @@ class CryptoAccount extends NativeObject {
       .asFunction();
 
   String getMasterFingerprint() {
-    final response = nativeGetMasterFingerprint(nativeObject).ref;
+    final response = nativeGetMasterFingerprint(nativeObject);
     return response.getString();
   }
 
   List<CryptoHDKey> getKeys() {
-    Response response = nativeGetLength(nativeObject).ref;
-    final length = response.getUint32();
+    final length = nativeGetLength(nativeObject).getUint32();
     List<CryptoHDKey> cryptoHDKeys = [];
     for (int i = 0; i < length; i++) {
-      response = getAccount(nativeObject, i).ref;
-      cryptoHDKeys.add(CryptoOutput(response.getObject()).getKey());
+      cryptoHDKeys.add(CryptoOutput(getAccount(nativeObject, i).getObject()).getKey());
     }
     return cryptoHDKeys;
   }
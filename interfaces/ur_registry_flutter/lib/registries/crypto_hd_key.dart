@@ -57,28 +57,28 @@ class CryptoHDKey extends NativeObject {
       .asFunction();
 
   String getKeyData() {
-    final response = nativeGetKeyData(nativeObject).ref;
+    final response = nativeGetKeyData(nativeObject);
     return response.getString();
   }
 
   int getAccountIndex(int level) {
-    final response = fnGetAccountIndex(nativeObject, level).ref;
+    final response = fnGetAccountIndex(nativeObject, level);
     return response.getUint32();
   }
 
   String getName() {
-    final response = nativeGetName(nativeObject).ref;
+    final response = nativeGetName(nativeObject);
     return response.getString();
   }
 
   String getPath() {
-    final response = nativeGetPath(nativeObject).ref;
+    final response = nativeGetPath(nativeObject);
     return response.getString();
   }
 
   String? getChildrenPath() {
     try {
-      final response = nativeGetChildrenPath(nativeObject).ref;
+      final response = nativeGetChildrenPath(nativeObject);
       return response.getString();
     } catch (e) {
       return null;
@@ -86,24 +86,24 @@ class CryptoHDKey extends NativeObject {
   }
 
   String getSourceFingerprint() {
-    final response = nativeGetSourceFingerprint(nativeObject).ref;
+    final response = nativeGetSourceFingerprint(nativeObject);
     return response.getString();
   }
 
   int getDepth() {
-    final response = nativeGetDepth(nativeObject).ref;
+    final response = nativeGetDepth(nativeObject);
     return response.getUint32();
   }
 
   String? getChainCode() {
     try {
-      return nativeGetChainCode(nativeObject).ref.getString();
+      return nativeGetChainCode(nativeObject).getString();
     } catch (e) {
       return null;
     }
   }
 
   String getBip32Xpub() {
-    return nativeGetBip32Xpub(nativeObject).ref.getString();
+    return nativeGetBip32Xpub(nativeObject).getString();
   }
 }
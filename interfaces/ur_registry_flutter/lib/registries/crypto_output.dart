@@ -18,7 +18,7 @@ class CryptoOutput extends NativeObject {
       .asFunction();
 
   CryptoHDKey getKey() {
-    final response = nativeGetKey(nativeObject).ref;
+    final response = nativeGetKey(nativeObject);
     return CryptoHDKey(response.getObject());
   }
 }
@@ -32,17 +32,17 @@ class CryptoPSBT extends NativeObject {
 
   CryptoPSBT.factory(List<int> psbt) : super() {
     final psbtStr = hex.encode(psbt);
-    final response = nativeConstruct(psbtStr.toNativeUtf8()).ref;
+    final response = nativeConstruct(psbtStr.toNativeUtf8());
     nativeObject = response.getObject();
   }
 
   UREncoder toUREncoder() {
-    final response = nativeGetUREncoder(nativeObject).ref;
+    final response = nativeGetUREncoder(nativeObject);
     return UREncoder(response.getObject());
   }
 
   String getData() {
-    final response = nativeGetData(nativeObject).ref;
+    final response = nativeGetData(nativeObject);
     return response.getString();
   }
 }
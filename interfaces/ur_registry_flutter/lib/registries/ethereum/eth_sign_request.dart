@@ -57,7 +57,7 @@ class EthSignRequest extends NativeObject {
 
   EthSignRequest(Pointer<Void> object) : super() {
     nativeObject = object;
-    final response = nativeGetRequestId(nativeObject).ref;
+    final response = nativeGetRequestId(nativeObject);
     final uuidBuffer = response.getString();
     uuid = Uuid.unparse(hex.decode(uuidBuffer));
   }
@@ -85,13 +85,12 @@ class EthSignRequest extends NativeObject {
             path.toNativeUtf8(),
             xfpInt,
             address.toNativeUtf8(),
-            origin.toNativeUtf8())
-        .ref;
+            origin.toNativeUtf8());
     nativeObject = response.getObject();
   }
 
   UREncoder toUREncoder() {
-    final response = nativeGetUREncoder(nativeObject).ref;
+    final response = nativeGetUREncoder(nativeObject);
     return UREncoder(response.getObject());
   }
 
@@ -25,13 +25,13 @@ class EthSignature extends NativeObject {
       .asFunction();
 
   String getRequestId() {
-    final response = nativeGetRequestId(nativeObject).ref;
+    final response = nativeGetRequestId(nativeObject);
     final String requestIdBufferStr = response.getString();
     return Uuid.unparse(hex.decode(requestIdBufferStr));
   }
 
   List<int> getSignature() {
-    final response = nativeGetSignature(nativeObject).ref;
+    final response = nativeGetSignature(nativeObject);
     final String signature = response.getString();
     return hex.decode(signature);
   }
@@ -36,24 +36,20 @@ class CryptoMultiAccounts extends NativeObject {
       .asFunction();
 
   String getDevice() {
-    final response = nativeGetDevice(nativeObject).ref;
+    final response = nativeGetDevice(nativeObject);
     return response.getString();
   }
 
   String getMasterFingerprint() {
-    final response = nativeGetMasterFingerprint(nativeObject).ref;
+    final response = nativeGetMasterFingerprint(nativeObject);
     return response.getString();
   }
 
   List<CryptoHDKey> getKeys() {
-    Response response = getKeysLen(nativeObject).ref;
-    response.throwIfPresent();
-    final length = response.data.getUInt32();
+    final length = getKeysLen(nativeObject).getUint32();
     List<CryptoHDKey> cryptoHDKeys = [];
     for (int i = 0; i < length; i++) {
-      response = getKey(nativeObject, i).ref;
-      response.throwIfPresent();
-      cryptoHDKeys.add(CryptoHDKey(response.data.getObject()));
+      cryptoHDKeys.add(CryptoHDKey(getKey(nativeObject, i).getObject()));
     }
     return cryptoHDKeys;
   }
@@ -35,13 +35,13 @@ class SolSignRequest extends NativeObject {
 
   SolSignRequest(Pointer<Void> object) : super(){
     nativeObject = object;
-    final response = nativeGetRequestId(nativeObject).ref;
+    final response = nativeGetRequestId(nativeObject);
     final uuidBuffer = response.getString();
     uuid = Uuid.unparse(hex.decode(uuidBuffer));
   }
 
   // SolSignRequest._internal(): super() {
-  //   final response = nativeNew().ref;
+  //   final response = nativeNew();
   //   nativeInstance = response.getObject();
   // }
 
@@ -61,13 +61,12 @@ class SolSignRequest extends NativeObject {
         xfpInt,
         pubkeyStr.toNativeUtf8(),
         origin.toNativeUtf8(),
-        signType)
-        .ref;
+        signType);
     nativeObject = response.getObject();
   }
 
   UREncoder toUREncoder() {
-    final response = nativeGetUREncoder(nativeObject).ref;
+    final response = nativeGetUREncoder(nativeObject);
     return UREncoder(response.getObject());
   }
 }
@@ -1,12 +1,23 @@
+import 'dart:typed_data';
+
 import 'package:ur_registry_flutter/ffi/ffi_factory.dart';
 
 const success = 0;
-const error = 1;
 
-const typeObject = "OBJECT";
 const typeBoolean = "BOOLEAN";
 const typeUInt32 = "UINT32";
 const typeString = "STRING";
+const typeBytes = "BYTES";
+const typeNull = "NULL";
+
+const primitiveTypes = [typeBoolean, typeUInt32, typeString, typeBytes, typeNull];
+
+class Bytes extends Struct {
+  late Pointer<Uint8> data;
+
+  @Size()
+  late int len;
+}
 
 class Data extends Union {
   late Pointer<Void> _object;
@@ -19,6 +30,8 @@ class Data extends Union {
 
   late Pointer<Utf8> _string;
 
+  late Bytes _bytes;
+
   late Pointer<Void> _null;
 
   String getString() {
@@ -57,16 +70,21 @@ class Response extends Struct {
   }
 
   bool isError() {
-    return statusCode == error;
+    return statusCode != success;
   }
 
   void checkValueType(String target) {
     if(valueType.toDartString() != target) throw Exception("Wrong response type, expected $target, received ${valueType.toDartString()}");
   }
 
+  void checkObjectType() {
+    final type = valueType.toDartString();
+    if (primitiveTypes.contains(type)) throw Exception("Wrong response type, expected an object, received $type");
+  }
+
   Pointer<Void> getObject() {
     throwIfPresent();
-    checkValueType(typeObject);
+    checkObjectType();
     return data._object;
   }
 
@@ -88,6 +106,14 @@ class Response extends Struct {
     return data._string.toDartString();
   }
 
+  Uint8List getBytes() {
+    throwIfPresent();
+    checkValueType(typeBytes);
+    final bytes = data._bytes;
+    if (bytes.len == 0) return Uint8List(0);
+    return Uint8List.fromList(bytes.data.asTypedList(bytes.len));
+  }
+
   void throwIfPresent() {
     if (isError()) {
       throw Exception(getErrorMessage());
@@ -98,3 +124,21 @@ class Response extends Struct {
     return errorMessage.toDartString();
   }
 }
+
+extension ResponsePointer on Pointer<Response> {
+  T consume<T>(T Function(Response response) read) {
+    return read(ref);
+  }
+
+  Pointer<Void> getObject() => consume((response) => response.getObject());
+
+  bool getBoolean() => consume((response) => response.getBoolean());
+
+  int getUint32() => consume((response) => response.getUint32());
+
+  String getString() => consume((response) => response.getString());
+
+  Uint8List getBytes() => consume((response) => response.getBytes());
+
+  void throwIfPresent() => consume((response) => response.throwIfPresent());
+}
@@ -1,12 +1,24 @@
+import 'dart:typed_data';
+
 import 'package:ur_registry_flutter/ffi/ffi_factory.dart';
+import 'package:ur_registry_flutter/ur_registry_flutter.dart';
 
 const success = 0;
-const error = 1;
 
-const typeObject = "OBJECT";
 const typeBoolean = "BOOLEAN";
 const typeUInt32 = "UINT32";
 const typeString = "STRING";
+const typeBytes = "BYTES";
+const typeNull = "NULL";
+
+const primitiveTypes = [typeBoolean, typeUInt32, typeString, typeBytes, typeNull];
+
+base class Bytes extends Struct {
+  external Pointer<Uint8> data;
+
+  @Size()
+  external int len;
+}
 
 base class Data extends Union {
   external Pointer<Void> _object;
@@ -19,6 +31,8 @@ base class Data extends Union {
 
   external Pointer<Utf8> _string;
 
+  external Bytes _bytes;
+
   external Pointer<Void> _null;
 
   String getString() {
@@ -57,16 +71,21 @@ base class Response extends Struct {
   }
 
   bool isError() {
-    return statusCode == error;
+    return statusCode != success;
   }
 
   void checkValueType(String target) {
     if(valueType.toDartString() != target) throw Exception("Wrong response type, expected $target, received ${valueType.toDartString()}");
   }
 
+  void checkObjectType() {
+    final type = valueType.toDartString();
+    if (primitiveTypes.contains(type)) throw Exception("Wrong response type, expected an object, received $type");
+  }
+
   Pointer<Void> getObject() {
     throwIfPresent();
-    checkValueType(typeObject);
+    checkObjectType();
     return data._object;
   }
 
@@ -88,6 +107,14 @@ base class Response extends Struct {
     return data._string.toDartString();
   }
 
+  Uint8List getBytes() {
+    throwIfPresent();
+    checkValueType(typeBytes);
+    final bytes = data._bytes;
+    if (bytes.len == 0) return Uint8List(0);
+    return Uint8List.fromList(bytes.data.asTypedList(bytes.len));
+  }
+
   void throwIfPresent() {
     if (isError()) {
       throw Exception(getErrorMessage());
@@ -98,3 +125,34 @@ base class Response extends Struct {
     return errorMessage.toDartString();
   }
 }
+
+typedef NativeResponseFree = Void Function(Pointer<Response>);
+typedef ResponseFree = void Function(Pointer<Response>);
+
+final ResponseFree _responseFree = UrRegistryFlutter.load()
+    .lookup<NativeFunction<NativeResponseFree>>("response_free")
+    .asFunction();
+
+/// Every native call hands out a `Response` owned by the caller. These getters copy the value
+/// out and release the response with `response_free`, also when reading it throws.
+extension ResponsePointer on Pointer<Response> {
+  T consume<T>(T Function(Response response) read) {
+    try {
+      return read(ref);
+    } finally {
+      _responseFree(this);
+    }
+  }
+
+  Pointer<Void> getObject() => consume((response) => response.getObject());
+
+  bool getBoolean() => consume((response) => response.getBoolean());
+
+  int getUint32() => consume((response) => response.getUint32());
+
+  String getString() => consume((response) => response.getString());
+
+  Uint8List getBytes() => consume((response) => response.getBytes());
+
+  void throwIfPresent() => consume((response) => response.throwIfPresent());
+}
@@ -90,22 +90,22 @@ class URDecoder extends NativeObject {
       .asFunction();
 
   URDecoder() : super() {
-    final response = nativeNew().ref;
+    final response = nativeNew();
     nativeObject = response.getObject();
   }
 
   void receive(String ur) {
-    final response = nativeReceive(nativeObject, ur.toNativeUtf8()).ref;
+    final response = nativeReceive(nativeObject, ur.toNativeUtf8());
     response.throwIfPresent();
   }
 
   bool isComplete() {
-    final response = nativeIsComplete(nativeObject).ref;
+    final response = nativeIsComplete(nativeObject);
     return response.getBoolean();
   }
 
   String result() {
-    final response = nativeResult(nativeObject).ref;
+    final response = nativeResult(nativeObject);
     return response.getString();
   }
 
@@ -113,57 +113,53 @@ class URDecoder extends NativeObject {
     switch (type) {
       case SupportedType.cryptoHDKey:
         final response =
-            nativeResolve(nativeObject, _cryptoHDKey.toNativeUtf8()).ref;
+            nativeResolve(nativeObject, _cryptoHDKey.toNativeUtf8());
         return CryptoHDKey(response.getObject());
       case SupportedType.cryptoAccount:
         final response =
-            nativeResolve(nativeObject, _cryptoAccount.toNativeUtf8()).ref;
+            nativeResolve(nativeObject, _cryptoAccount.toNativeUtf8());
         return CryptoAccount(response.getObject());
       case SupportedType.cryptoPSBT:
         final response =
-            nativeResolve(nativeObject, _cryptoPSBT.toNativeUtf8()).ref;
+            nativeResolve(nativeObject, _cryptoPSBT.toNativeUtf8());
         return CryptoPSBT(response.getObject());
       case SupportedType.cryptoMultiAccounts:
         final response =
-            nativeResolve(nativeObject, _cryptoMultiAccounts.toNativeUtf8())
-                .ref;
+            nativeResolve(nativeObject, _cryptoMultiAccounts.toNativeUtf8());
         return CryptoMultiAccounts(response.getObject());
       // sol
       case SupportedType.solSignRequest:
         final response =
-            nativeResolve(nativeObject, _solSignRequest.toNativeUtf8()).ref;
+            nativeResolve(nativeObject, _solSignRequest.toNativeUtf8());
         return SolSignRequest(response.getObject());
       case SupportedType.solSignature:
         final response =
-            nativeResolve(nativeObject, _solSignature.toNativeUtf8()).ref;
+            nativeResolve(nativeObject, _solSignature.toNativeUtf8());
         return SolSignature(response.getObject());
       // eth
       case SupportedType.ethSignRequest:
         final response =
-            nativeResolve(nativeObject, _ethSignRequest.toNativeUtf8()).ref;
+            nativeResolve(nativeObject, _ethSignRequest.toNativeUtf8());
         return EthSignRequest(response.getObject());
       case SupportedType.ethSignature:
         final response =
-            nativeResolve(nativeObject, _ethSignature.toNativeUtf8()).ref;
+            nativeResolve(nativeObject, _ethSignature.toNativeUtf8());
         return EthSignature(response.getObject());
       case SupportedType.cardanoSignature:
         final response =
-            nativeResolve(nativeObject, _cardanoSignature.toNativeUtf8()).ref;
+            nativeResolve(nativeObject, _cardanoSignature.toNativeUtf8());
         return CardanoSignature(response.getObject());
       case SupportedType.cardanoSignDataSignature:
         final response = nativeResolve(
-                nativeObject, _cardanoSignDataSignature.toNativeUtf8())
-            .ref;
+                nativeObject, _cardanoSignDataSignature.toNativeUtf8());
         return CardanoSignDataSignature(response.getObject());
       case SupportedType.cardanoSignCip8DataSignature:
         final response = nativeResolve(
-                nativeObject, _cardanoSignCip8DataSignature.toNativeUtf8())
-            .ref;
+                nativeObject, _cardanoSignCip8DataSignature.toNativeUtf8());
         return CardanoSignCip8DataSignature(response.getObject());
       case SupportedType.cardanoCatalystSignature:
         final response = nativeResolve(nativeObject,
-                _cardanoCatalystVotingRegistrationSignature.toNativeUtf8())
-            .ref;
+                _cardanoCatalystVotingRegistrationSignature.toNativeUtf8());
         return CardanoCatalystSignature(response.getObject());
       default:
         throw Exception("type $type is not supported");
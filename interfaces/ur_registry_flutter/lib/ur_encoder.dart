@@ -16,7 +16,7 @@ class UREncoder extends NativeObject {
   }
 
   String nextPart() {
-    final response = nativeNextPart(nativeObject).ref;
+    final response = nativeNextPart(nativeObject);
     final resultStr = response.getString().toUpperCase();
     return resultStr;
   }
@@ -0,0 +1,289 @@
+use crate::error::{Error, ErrorKind};
+use crate::handle::with_object;
+use crate::qr::{encode_matrix, QrMatrix};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::PtrVoid;
+use crate::ur_encoder::UREncoder;
+use std::borrow::Cow;
+
+pub const FORMAT_GIF: u32 = 0;
+pub const FORMAT_APNG: u32 = 1;
+pub const MAX_FRAMES: u32 = 1000;
+pub const MAX_SIZE: u32 = 4096;
+/// Bounds `frames * size * size`, so long animations have to use smaller frames.
+pub const MAX_TOTAL_PIXELS: u64 = 256 * 1024 * 1024;
+
+const EC_LEVEL_L: u32 = 0;
+const QUIET_ZONE: u32 = 4;
+
+struct Animation {
+    size: usize,
+    module_size: usize,
+    frame_interval_ms: u16,
+    matrices: Vec<QrMatrix>,
+}
+
+impl Animation {
+    /// Draws every matrix at the same module size so that frames whose sequence numbers need a
+    /// larger QR version line up with the others.
+    fn new(matrices: Vec<QrMatrix>, size: usize, frame_interval_ms: u16) -> Result<Self, Error> {
+        let width = matrices.iter().map(|m| m.width).max().unwrap_or(1);
+        let module_size = size / width;
+        if module_size == 0 {
+            return Err(Error::invalid_argument(
+                "qr_size",
+                format!("QR size must be at least {} pixels", width),
+            ));
+        }
+        Ok(Animation {
+            size,
+            module_size,
+            frame_interval_ms,
+            matrices,
+        })
+    }
+
+    /// One 8-bit luma buffer of `size * size` pixels with the matrix centered on white, frames
+    /// are rendered one at a time as the encoder consumes them.
+    fn render_frame(&self, matrix: &QrMatrix) -> Vec<u8> {
+        let (size, module_size) = (self.size, self.module_size);
+        let offset = (size - matrix.width * module_size) / 2;
+        let mut pixels = vec![255u8; size * size];
+        for y in 0..matrix.width * module_size {
+            for x in 0..matrix.width * module_size {
+                if matrix.is_dark(x / module_size, y / module_size) {
+                    pixels[(y + offset) * size + x + offset] = 0;
+                }
+            }
+        }
+        pixels
+    }
+
+    fn to_gif(&self) -> Result<Vec<u8>, Error> {
+        let internal = |e: gif::EncodingError| Error::new(ErrorKind::Internal, e);
+        let size = self.size as u16;
+        let mut gif = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut gif, size, size, &[0, 0, 0, 255, 255, 255]).map_err(internal)?;
+            encoder.set_repeat(gif::Repeat::Infinite).map_err(internal)?;
+            for matrix in &self.matrices {
+                let pixels = self.render_frame(matrix);
+                let mut frame = gif::Frame {
+                    width: size,
+                    height: size,
+                    delay: ((u32::from(self.frame_interval_ms) + 5) / 10) as u16,
+                    ..gif::Frame::default()
+                };
+                frame.buffer = Cow::Owned(pixels.iter().map(|p| u8::from(*p == 255)).collect());
+                encoder.write_frame(&frame).map_err(internal)?;
+            }
+        }
+        Ok(gif)
+    }
+
+    fn to_apng(&self) -> Result<Vec<u8>, Error> {
+        let internal = |e: png::EncodingError| Error::new(ErrorKind::Internal, e);
+        let mut png = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png, self.size as u32, self.size as u32);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.set_animated(self.matrices.len() as u32, 0).map_err(internal)?;
+            encoder.set_frame_delay(self.frame_interval_ms, 1000).map_err(internal)?;
+            let mut writer = encoder.write_header().map_err(internal)?;
+            for matrix in &self.matrices {
+                writer.write_image_data(&self.render_frame(matrix)).map_err(internal)?;
+            }
+            writer.finish().map_err(internal)?;
+        }
+        Ok(png)
+    }
+}
+
+/// Renders the next `frame_count` parts of the encoder, or all fragments plus
+/// `extra_fountain_parts` when `frame_count` is `0`.
+fn export_animation(
+    encoder: &mut UREncoder,
+    format: u32,
+    frame_count: u32,
+    extra_fountain_parts: u32,
+    frame_interval_ms: u32,
+    qr_size: u32,
+) -> Result<Vec<u8>, Error> {
+    if format != FORMAT_GIF && format != FORMAT_APNG {
+        return Err(Error::invalid_argument(
+            "format",
+            format!("format {} is invalid, expected 0 (GIF) or 1 (APNG)", format),
+        ));
+    }
+    let frame_count = match frame_count {
+        0 => (encoder.fragment_count() as u32).saturating_add(extra_fountain_parts),
+        v => v,
+    };
+    if frame_count > MAX_FRAMES {
+        return Err(Error::invalid_argument(
+            "frame_count",
+            format!("an animation can have at most {} frames", MAX_FRAMES),
+        ));
+    }
+    if !(10..=u16::MAX as u32).contains(&frame_interval_ms) {
+        return Err(Error::invalid_argument(
+            "frame_interval_ms",
+            format!("frame interval must be between 10 and {} ms", u16::MAX),
+        ));
+    }
+    if !(1..=MAX_SIZE).contains(&qr_size) {
+        return Err(Error::invalid_argument(
+            "qr_size",
+            format!("QR size must be between 1 and {} pixels", MAX_SIZE),
+        ));
+    }
+    let total_pixels = u64::from(frame_count) * u64::from(qr_size) * u64::from(qr_size);
+    if total_pixels > MAX_TOTAL_PIXELS {
+        return Err(Error::new(
+            ErrorKind::LimitExceeded,
+            format!(
+                "{} frames of {} pixels exceed the budget of {} pixels",
+                frame_count, qr_size, MAX_TOTAL_PIXELS
+            ),
+        ));
+    }
+    // parts that end up in no animation are handed out again by the next export
+    let seq_num = encoder.seq_num();
+    let animation = (0..frame_count)
+        .map(|_| encoder.next_part().and_then(|part| encode_matrix(&part, EC_LEVEL_L, QUIET_ZONE)))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|matrices| Animation::new(matrices, qr_size as usize, frame_interval_ms as u16))
+        .and_then(|animation| match format {
+            FORMAT_GIF => animation.to_gif(),
+            _ => animation.to_apng(),
+        });
+    if animation.is_err() {
+        encoder.rewind(seq_num)?;
+    }
+    animation
+}
+
+/// Renders the upcoming parts of an encoder as an animated GIF (`format` 0) or APNG (`format` 1)
+/// of `qr_size` pixels square. The encoder advances past every part that was rendered and is left
+/// as it was when the export fails. Frames are limited to `MAX_TOTAL_PIXELS` in total, larger
+/// requests fail with `LimitExceeded`. GIF delays are in hundredths of a second, so
+/// `frame_interval_ms` is rounded to the nearest 10 ms there.
+#[no_mangle]
+pub extern "C" fn ur_encoder_export_animation(
+    ur_encoder: PtrVoid,
+    format: u32,
+    frame_count: u32,
+    extra_fountain_parts: u32,
+    frame_interval_ms: u32,
+    qr_size: u32,
+) -> PtrResponse {
+    catch_panic(|| {
+        with_object(ur_encoder, |ur_encoder: &mut UREncoder| {
+            match export_animation(ur_encoder, format, frame_count, extra_fountain_parts, frame_interval_ms, qr_size) {
+                Ok(v) => Response::success_bytes(v),
+                Err(e) => Response::error(e),
+            }
+        })
+        .c_ptr()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::SUCCESS;
+    use crate::ur_decoder::{
+        ur_decoder_free, ur_decoder_is_complete, ur_decoder_new, ur_decoder_receive, ur_decoder_result,
+    };
+    use crate::ur_encoder::{new_encoder, EncoderOptions};
+    use crate::utils::str_to_ptr_c_char;
+    use std::ffi::CStr;
+
+    fn read_qr(size: usize, pixels: &[u8]) -> String {
+        let mut image = rqrr::PreparedImage::prepare_from_greyscale(size, size, |x, y| pixels[y * size + x]);
+        let grids = image.detect_grids();
+        assert_eq!(grids.len(), 1);
+        grids[0].decode().unwrap().1
+    }
+
+    fn decode_frames(parts: Vec<String>, message: &[u8]) {
+        let decoder = Response::from_ptr(ur_decoder_new()).value.get_object();
+        for part in parts {
+            let response = Response::from_ptr(ur_decoder_receive(decoder, str_to_ptr_c_char(part)));
+            assert_eq!(response.status_code, SUCCESS);
+        }
+        assert!(Response::from_ptr(ur_decoder_is_complete(decoder)).value.get_boolean());
+        let response = Response::from_ptr(ur_decoder_result(decoder));
+        let result = unsafe { CStr::from_ptr(response.value.get_string()) };
+        assert_eq!(result.to_str().unwrap(), hex::encode(message));
+        assert_eq!(Response::from_ptr(ur_decoder_free(decoder)).status_code, SUCCESS);
+    }
+
+    fn encoder(message: &[u8]) -> UREncoder {
+        new_encoder(message, "bytes".to_string(), EncoderOptions::new(100, 0, 0)).unwrap()
+    }
+
+    #[test]
+    fn test_gif_round_trip() {
+        let message: Vec<u8> = (0..500).map(|i| i as u8).collect();
+        let mut encoder = encoder(&message);
+        let gif = export_animation(&mut encoder, FORMAT_GIF, 0, 2, 205, 400).unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(gif.as_slice()).unwrap();
+        let mut parts = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 21);
+            let pixels: Vec<u8> = frame.buffer.iter().map(|p| p * 255).collect();
+            parts.push(read_qr(400, &pixels));
+        }
+        assert_eq!(parts.len(), encoder.fragment_count() + 2);
+        decode_frames(parts, &message);
+    }
+
+    #[test]
+    fn test_apng_round_trip() {
+        let message: Vec<u8> = (0..500).map(|i| i as u8).collect();
+        let mut encoder = encoder(&message);
+        let apng = export_animation(&mut encoder, FORMAT_APNG, 0, 0, 100, 300).unwrap();
+
+        let mut reader = png::Decoder::new(apng.as_slice()).read_info().unwrap();
+        let frame_count = reader.info().animation_control.unwrap().num_frames;
+        assert_eq!(frame_count as usize, encoder.fragment_count());
+        let mut parts = vec![];
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        for _ in 0..frame_count {
+            reader.next_frame(&mut pixels).unwrap();
+            parts.push(read_qr(300, &pixels));
+        }
+        decode_frames(parts, &message);
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let message = vec![0u8; 500];
+        let kind = |format, frame_count, interval, size| {
+            export_animation(&mut encoder(&message), format, frame_count, 0, interval, size)
+                .err()
+                .map(|e| e.kind())
+        };
+        assert_eq!(kind(2, 0, 100, 400), Some(ErrorKind::InvalidArgument));
+        assert_eq!(kind(FORMAT_GIF, MAX_FRAMES + 1, 100, 400), Some(ErrorKind::InvalidArgument));
+        assert_eq!(kind(FORMAT_GIF, 0, 0, 400), Some(ErrorKind::InvalidArgument));
+        assert_eq!(kind(FORMAT_GIF, 0, 100, 10), Some(ErrorKind::InvalidArgument));
+        assert_eq!(kind(FORMAT_GIF, 0, 100, MAX_SIZE + 1), Some(ErrorKind::InvalidArgument));
+        assert_eq!(kind(FORMAT_GIF, 17, 100, MAX_SIZE), Some(ErrorKind::LimitExceeded));
+        assert_eq!(kind(FORMAT_APNG, MAX_FRAMES, 100, 600), Some(ErrorKind::LimitExceeded));
+
+        // a QR size too small for the parts fails once they are encoded, without skipping them
+        let mut encoder = encoder(&message);
+        let first = encoder.next_part().unwrap();
+        encoder.rewind(0).unwrap();
+        let error = export_animation(&mut encoder, FORMAT_GIF, 3, 0, 100, 10).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidArgument);
+        assert_eq!(encoder.seq_num(), 0);
+        assert_eq!(encoder.next_part().unwrap(), first);
+    }
+}
@@ -0,0 +1,1058 @@
+use crate::animation::ur_encoder_export_animation;
+use crate::cardano::cardano_catalyst_signature::*;
+use crate::cardano::cardano_catalyst_voting_registration::*;
+use crate::cardano::cardano_sign_cip8_data_request::*;
+use crate::cardano::cardano_sign_cip8_data_signature::*;
+use crate::cardano::cardano_sign_data_request::*;
+use crate::cardano::cardano_sign_data_signature::*;
+use crate::cardano::cardano_sign_request::*;
+use crate::cardano::cardano_sign_tx_hash_request::*;
+use crate::cardano::cardano_signature::*;
+use crate::crypto_account::*;
+use crate::crypto_hd_key::*;
+use crate::crypto_output::*;
+use crate::crypto_psbt::*;
+use crate::ethereum::eip712::*;
+use crate::ethereum::eth_abi::*;
+use crate::ethereum::eth_sign_request::*;
+use crate::ethereum::eth_signarure::*;
+use crate::ethereum::eth_signer::*;
+use crate::ethereum::eth_summary::*;
+use crate::extend::crypto_multi_accounts::*;
+use crate::qr::*;
+use crate::error::ErrorKind;
+use crate::response::{PtrResponse, Response, SUCCESS};
+use crate::solana::solana_sign_request::*;
+use crate::solana::solana_signarure::*;
+use crate::types::{PtrString, PtrVoid};
+use crate::ur_decoder::*;
+use crate::ur_decoder_session::*;
+use crate::ur_encoder::*;
+use crate::ur_part::{encode_payload, ur_parse};
+use crate::utils::str_to_ptr_c_char;
+use std::ffi::{CStr, CString};
+use std::ptr::{null, null_mut};
+
+const REQUEST_ID: &str = "9b1deb4d-3b7d-4bad-9bdd-2b0d7b3dcb6d";
+
+fn assert_error(response: PtrResponse) {
+    let response = Response::from_ptr(response);
+    assert_ne!(response.status_code, SUCCESS);
+    assert!(!response.error_message.is_null());
+}
+
+fn c_str(s: &str) -> PtrString {
+    str_to_ptr_c_char(s.to_string())
+}
+
+fn invalid_utf8() -> PtrString {
+    CString::new(vec![0xc3, 0x28]).unwrap().into_raw()
+}
+
+fn object(response: PtrResponse) -> PtrVoid {
+    let response = Response::from_ptr(response);
+    assert_eq!(response.status_code, SUCCESS);
+    response.value.get_object()
+}
+
+fn string(response: PtrResponse) -> String {
+    let response = Response::from_ptr(response);
+    assert_eq!(response.status_code, SUCCESS);
+    unsafe { CStr::from_ptr(response.value.get_string()) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+fn uint32(response: PtrResponse) -> u32 {
+    let response = Response::from_ptr(response);
+    assert_eq!(response.status_code, SUCCESS);
+    response.value.get_uint32()
+}
+
+fn boolean(response: PtrResponse) -> bool {
+    let response = Response::from_ptr(response);
+    assert_eq!(response.status_code, SUCCESS);
+    response.value.get_boolean()
+}
+
+fn malformed_strings() -> Vec<PtrString> {
+    vec![null_mut(), invalid_utf8(), c_str("zz")]
+}
+
+fn malformed_handles(wrong_type: PtrVoid) -> Vec<PtrVoid> {
+    vec![null_mut(), usize::MAX as PtrVoid, wrong_type]
+}
+
+#[test]
+fn test_getters_reject_malformed_handles() {
+    let getters: Vec<extern "C" fn(PtrVoid) -> PtrResponse> = vec![
+        cardano_catalyst_signature_get_request_id,
+        cardano_catalyst_signature_get_signature,
+        cardano_sign_cip8_data_signature_get_request_id,
+        cardano_sign_cip8_data_signature_get_signature,
+        cardano_sign_cip8_data_signature_get_public_key,
+        cardano_sign_cip8_data_signature_get_address_field,
+        cardano_sign_data_signature_get_request_id,
+        cardano_sign_data_signature_get_signature,
+        cardano_sign_data_signature_get_public_key,
+        cardano_sign_request_get_ur_encoder,
+        cardano_sign_request_get_request_id,
+        cardano_signature_get_witness_set,
+        cardano_signature_get_witness_set_bytes,
+        cardano_signature_get_request_id,
+        crypto_account_get_accounts_len,
+        crypto_account_get_master_fingerprint,
+        crypto_hd_key_get_key_data,
+        crypto_hd_key_get_chain_code,
+        crypto_hd_key_get_name,
+        crypto_hd_key_get_path,
+        crypto_hd_key_get_children_path,
+        crypto_hd_key_get_source_fingerprint,
+        crypto_hd_key_get_depth,
+        crypto_hd_key_get_note,
+        crypto_hd_key_get_bip32_xpub,
+        crypto_output_get_hd_key,
+        crypto_psbt_get_data,
+        crypto_psbt_get_data_bytes,
+        crypto_psbt_get_ur_encoder,
+        eth_sign_request_get_ur_encoder,
+        eth_sign_request_get_request_id,
+        eth_sign_request_get_sign_data,
+        eth_sign_request_get_sign_data_bytes,
+        eth_sign_request_get_data_type,
+        eth_sign_request_get_chain_id,
+        eth_sign_request_get_derivation_path,
+        eth_sign_request_get_source_fingerprint,
+        eth_sign_request_get_address,
+        eth_sign_request_get_origin,
+        eth_signature_get_signature,
+        eth_signature_get_signature_bytes,
+        eth_signature_get_request_id,
+        extend_crypto_multi_accounts_get_master_fingerprint,
+        extend_crypto_multi_accounts_get_device,
+        extend_crypto_multi_accounts_get_keys_len,
+        solana_sign_request_get_ur_encoder,
+        solana_sign_request_get_request_id,
+        solana_signature_get_signature,
+        solana_signature_get_request_id,
+        ur_encoder_next_part,
+        ur_encoder_fragment_count,
+        ur_encoder_current_sequence,
+        ur_encoder_is_single_part,
+        cardano_catalyst_signature_free,
+        cardano_catalyst_voting_registration_free,
+        cardano_sign_cip8_data_request_free,
+        cardano_sign_cip8_data_signature_free,
+        cardano_sign_data_request_free,
+        cardano_sign_data_signature_free,
+        cardano_sign_request_free,
+        cardano_sign_tx_hash_request_free,
+        cardano_signature_free,
+        crypto_account_free,
+        crypto_hd_key_free,
+        crypto_output_free,
+        crypto_psbt_free,
+        eth_sign_request_free,
+        eth_signature_free,
+        extend_crypto_multi_accounts_free,
+        solana_sign_request_free,
+        solana_signature_free,
+        ur_encoder_free,
+    ];
+    let wrong_type = object(ur_decoder_new());
+    for getter in getters {
+        for handle in malformed_handles(wrong_type) {
+            assert_error(getter(handle));
+        }
+    }
+
+    let indexed_getters: Vec<extern "C" fn(PtrVoid, u32) -> PtrResponse> = vec![
+        crypto_account_get_account,
+        crypto_hd_key_get_account_index,
+        extend_crypto_multi_accounts_get_key,
+    ];
+    for getter in indexed_getters {
+        for handle in malformed_handles(wrong_type) {
+            assert_error(getter(handle, 0));
+        }
+    }
+
+    let encoder_getters: Vec<extern "C" fn(PtrVoid, u32, u32, u32) -> PtrResponse> = vec![
+        cardano_sign_request_get_ur_encoder_with_options,
+        crypto_psbt_get_ur_encoder_with_options,
+        eth_sign_request_get_ur_encoder_with_options,
+        solana_sign_request_get_ur_encoder_with_options,
+    ];
+    for getter in encoder_getters {
+        for handle in malformed_handles(wrong_type) {
+            assert_error(getter(handle, 400, 0, 0));
+        }
+    }
+    for handle in malformed_handles(wrong_type) {
+        assert_error(ur_encoder_set_pure_sequence(handle, true));
+        assert_error(ur_encoder_export_animation(handle, 0, 0, 0, 100, 400));
+    }
+}
+
+#[test]
+fn test_decoder_rejects_malformed_input() {
+    let wrong_type = object(eth_sign_request_new());
+    let decoder_getters: Vec<extern "C" fn(PtrVoid) -> PtrResponse> = vec![
+        ur_decoder_is_complete,
+        ur_decoder_result,
+        ur_decoder_result_bytes,
+        ur_decoder_get_type,
+        ur_decoder_resolve_auto,
+        ur_decoder_expected_part_count,
+        ur_decoder_processed_parts_count,
+        ur_decoder_received_parts_count,
+        ur_decoder_estimated_percent_complete,
+        ur_decoder_reset,
+        ur_decoder_free,
+    ];
+    for getter in decoder_getters {
+        for handle in malformed_handles(wrong_type) {
+            assert_error(getter(handle));
+        }
+    }
+
+    let decoder = object(ur_decoder_new());
+    assert_error(ur_decoder_get_type(decoder));
+    assert_error(ur_decoder_resolve_auto(decoder));
+    for ur in malformed_strings() {
+        assert_error(ur_decoder_receive(decoder, ur));
+        assert_error(ur_decoder_resolve(decoder, ur));
+    }
+    let kind = |ur: &str| Response::from_ptr(ur_decoder_receive(decoder, c_str(ur))).status_code;
+    assert_eq!(kind("crypto-psbt/1-3/zzzz"), ErrorKind::InvalidScheme as u32);
+    assert_eq!(kind("ur:crypto-psbt/1-x/zzzz"), ErrorKind::InvalidSequence as u32);
+    assert_eq!(kind("ur:crypto-psbt/1-3/zzzz"), ErrorKind::InvalidBytewords as u32);
+    assert_error(ur_decoder_result(decoder));
+    for handle in malformed_handles(wrong_type) {
+        assert_error(ur_decoder_receive(handle, c_str("ur:bytes/hdcxzz")));
+        assert_error(ur_decoder_resolve(handle, c_str("crypto-psbt")));
+    }
+}
+
+#[test]
+fn test_constructors_reject_malformed_input() {
+    for s in malformed_strings() {
+        assert_error(crypto_psbt_construct(s));
+        assert_error(crypto_hd_key_get_uncompressed_key_data(s));
+        assert_error(eth_sign_request_construct(s, s, 1, 1, s, 0, s, s));
+        assert_error(solana_sign_request_construct(s, s, s, 0, s, s, 1));
+        assert_error(cardano_catalyst_signature_construct(s, s));
+        assert_error(cardano_sign_data_signature_construct(s, s, s));
+        assert_error(cardano_sign_cip8_data_signature_construct(s, s, s, s));
+        assert_error(cardano_sign_data_request_construct(s, s, s, s, s, s));
+        assert_error(cardano_sign_cip8_data_request_construct(s, s, s, s, s, s, false, s, 0));
+        assert_error(cardano_sign_request_construct(s, s, s, s, s));
+        assert_error(cardano_sign_tx_hash_request_construct(s, s, s, s, s));
+        assert_error(cardano_catalyst_voting_registration_construct(
+            s, s, s, s, s, s, 0, s, s, 0,
+        ));
+    }
+
+    assert_error(crypto_psbt_construct_from_bytes(null(), 4));
+    assert_error(eth_sign_request_construct_from_bytes(
+        c_str(REQUEST_ID), null(), 4, 1, 1, c_str("m/44'/60'/0'/0/0"), 0, c_str(""), c_str(""),
+    ));
+    assert_error(solana_sign_request_construct_from_bytes(
+        c_str(REQUEST_ID), null(), 4, c_str("m/44'/501'/0'"), 0, c_str(""), c_str(""), 1,
+    ));
+    assert_error(cardano_sign_request_construct_from_bytes(
+        c_str(REQUEST_ID), null(), 4, c_str("[]"), c_str("[]"), c_str(""),
+    ));
+
+    assert_error(crypto_hd_key_get_uncompressed_key_data(c_str("0")));
+    assert_error(crypto_hd_key_get_uncompressed_key_data(c_str("02")));
+    assert_error(cardano_sign_tx_hash_request_construct(
+        c_str(REQUEST_ID),
+        c_str("52a1f1c1a5a7e6ca0fdf0cd8f2a0fa2b7d83f5f8f37d1f3e5a8a0c50e8b0b4d7"),
+        c_str(r#"[{"xfp": "zz", "key_path": "m/1852'/1815'/0'/0/0"}]"#),
+        c_str(""),
+        c_str("[]"),
+    ));
+    assert_error(cardano_catalyst_voting_registration_construct(
+        c_str(REQUEST_ID),
+        c_str("73c5da0a"),
+        c_str(r#"[{"pub_key": "zz", "width": 1}]"#),
+        c_str("00"),
+        c_str("00"),
+        c_str("1"),
+        0,
+        c_str("m/1852'/1815'/0'/2/0"),
+        c_str(""),
+        0,
+    ));
+    assert_error(cardano_catalyst_voting_registration_construct(
+        c_str(REQUEST_ID),
+        c_str("73c5da0a"),
+        c_str("{"),
+        c_str("00"),
+        c_str("00"),
+        c_str("1"),
+        0,
+        c_str("m/1852'/1815'/0'/2/0"),
+        c_str(""),
+        0,
+    ));
+}
+
+#[test]
+fn test_strings_with_nul_bytes_are_rejected() {
+    let response = Response::success_string("origin\0suffix".to_string());
+    assert_eq!(response.status_code, ErrorKind::InvalidArgument as u32);
+    let message = unsafe { CStr::from_ptr(response.error_message) }.to_str().unwrap();
+    assert!(message.contains("position 6"), "{}", message);
+    assert_eq!(string(Response::success_string("origin".to_string()).c_ptr()), "origin");
+}
+
+#[test]
+fn test_bytes_round_trip() {
+    let psbt = vec![0x70, 0x73, 0x62, 0x74, 0xff];
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()));
+    let response = Response::from_ptr(crypto_psbt_get_data_bytes(crypto_psbt));
+    assert_eq!(response.status_code, SUCCESS);
+    let bytes = response.value.get_bytes();
+    assert_eq!(unsafe { std::slice::from_raw_parts(bytes.data, bytes.len) }, psbt.as_slice());
+
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(null(), 0));
+    let response = Response::from_ptr(crypto_psbt_get_data_bytes(crypto_psbt));
+    assert_eq!(response.value.get_bytes().len, 0);
+}
+
+#[test]
+fn test_decoder_resolves_by_received_type() {
+    let psbt = vec![0x70; 1000];
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()));
+    let encoder = object(crypto_psbt_get_ur_encoder(crypto_psbt));
+    let decoder = object(ur_decoder_new());
+    assert_eq!(uint32(ur_decoder_estimated_percent_complete(decoder)), 0);
+    while !boolean(ur_decoder_is_complete(decoder)) {
+        let part = string(ur_encoder_next_part(encoder));
+        assert_eq!(Response::from_ptr(ur_decoder_receive(decoder, c_str(&part))).status_code, SUCCESS);
+        assert!(uint32(ur_decoder_estimated_percent_complete(decoder)) > 0);
+    }
+    let expected = uint32(ur_decoder_expected_part_count(decoder));
+    assert!(expected > 1);
+    assert_eq!(uint32(ur_decoder_processed_parts_count(decoder)), expected);
+    assert_eq!(uint32(ur_decoder_received_parts_count(decoder)), expected);
+    assert_eq!(uint32(ur_decoder_estimated_percent_complete(decoder)), 100);
+    assert_eq!(string(ur_decoder_get_type(decoder)), "crypto-psbt");
+
+    let other = vec![0x71; 1000];
+    let other = object(crypto_psbt_construct_from_bytes(other.as_ptr(), other.len()));
+    let other_encoder = object(crypto_psbt_get_ur_encoder(other));
+    let other_part = string(ur_encoder_next_part(other_encoder));
+    let response = Response::from_ptr(ur_decoder_receive(decoder, c_str(&other_part)));
+    assert_eq!(response.status_code, ErrorKind::DifferentMessage as u32);
+
+    let response = Response::from_ptr(ur_decoder_resolve(decoder, c_str("eth-signature")));
+    assert_eq!(response.status_code, ErrorKind::TypeMismatch as u32);
+
+    let response = Response::from_ptr(ur_decoder_resolve_auto(decoder));
+    assert_eq!(response.status_code, SUCCESS);
+    let resolved = response.value.get_object();
+    let response = Response::from_ptr(crypto_psbt_get_data_bytes(resolved));
+    let bytes = response.value.get_bytes();
+    assert_eq!(unsafe { std::slice::from_raw_parts(bytes.data, bytes.len) }, psbt.as_slice());
+}
+
+#[test]
+fn test_decoder_reset() {
+    let psbt = vec![0x70; 1000];
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()));
+    let encoder = object(crypto_psbt_get_ur_encoder(crypto_psbt));
+    let decoder = object(ur_decoder_new());
+    let part = string(ur_encoder_next_part(encoder));
+    assert_eq!(Response::from_ptr(ur_decoder_receive(decoder, c_str(&part))).status_code, SUCCESS);
+    assert_eq!(uint32(ur_decoder_processed_parts_count(decoder)), 1);
+
+    let single = vec![0x70; 10];
+    let single = object(crypto_psbt_construct_from_bytes(single.as_ptr(), single.len()));
+    let encoder = object(crypto_psbt_get_ur_encoder(single));
+    let part = string(ur_encoder_next_part(encoder));
+    let response = Response::from_ptr(ur_decoder_receive(decoder, c_str(&part)));
+    assert_eq!(response.status_code, ErrorKind::DifferentMessage as u32);
+
+    assert_eq!(Response::from_ptr(ur_decoder_reset(decoder)).status_code, SUCCESS);
+    assert_eq!(uint32(ur_decoder_processed_parts_count(decoder)), 0);
+    assert_error(ur_decoder_get_type(decoder));
+    assert_eq!(Response::from_ptr(ur_decoder_receive(decoder, c_str(&part))).status_code, SUCCESS);
+    assert_eq!(uint32(ur_decoder_expected_part_count(decoder)), 1);
+}
+
+#[test]
+fn test_encoder_rejects_out_of_range_options() {
+    let psbt = vec![0x70; 1000];
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()));
+    assert_error(crypto_psbt_get_ur_encoder_with_options(crypto_psbt, 0, 0, 0));
+    assert_error(crypto_psbt_get_ur_encoder_with_options(crypto_psbt, u32::MAX, 0, 0));
+    assert_error(crypto_psbt_get_ur_encoder_with_options(crypto_psbt, 100, 200, 0));
+    assert_error(crypto_psbt_get_ur_encoder_with_options(crypto_psbt, 100, 0, u32::MAX));
+
+    let encoder = object(crypto_psbt_get_ur_encoder_with_options(crypto_psbt, 100, 0, 3));
+    assert!(string(ur_encoder_next_part(encoder)).starts_with("ur:crypto-psbt/3-"));
+}
+
+#[test]
+fn test_single_part_round_trip() {
+    let cbor = [0x83, 0x01, 0x02, 0x03];
+    let ur = string(ur_encode_single(c_str("custom-type"), cbor.as_ptr(), cbor.len()));
+    assert!(ur.starts_with("ur:custom-type/"));
+    let decoded: serde_json::Value = serde_json::from_str(&string(ur_decode_single(c_str(&ur.to_uppercase())))).unwrap();
+    assert_eq!(decoded, serde_json::json!({"type": "custom-type", "cbor": "83010203"}));
+
+    assert_error(ur_encode_single(c_str("Custom"), cbor.as_ptr(), cbor.len()));
+    assert_error(ur_encode_single(c_str("custom-type"), cbor.as_ptr(), 2));
+    for ur in malformed_strings() {
+        assert_error(ur_decode_single(ur));
+    }
+
+    let psbt = vec![0x70; 1000];
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()));
+    let encoder = object(crypto_psbt_get_ur_encoder(crypto_psbt));
+    let part = string(ur_encoder_next_part(encoder));
+    let response = Response::from_ptr(ur_decode_single(c_str(&part)));
+    assert_eq!(response.status_code, ErrorKind::NotSinglePart as u32);
+}
+
+#[test]
+fn test_ur_parse() {
+    let parse = |ur: &str| serde_json::from_str::<serde_json::Value>(&string(ur_parse(c_str(ur)))).unwrap();
+    let cbor = [0x83, 0x01, 0x02, 0x03];
+    let ur = string(ur_encode_single(c_str("custom-type"), cbor.as_ptr(), cbor.len()));
+    assert_eq!(
+        parse(&ur.to_uppercase()),
+        serde_json::json!({"scheme": "ur", "type": "custom-type", "multipart": false, "seq": 1, "seqLen": 1, "checksumValid": true})
+    );
+    let other = if ur.ends_with("ae") { "ad" } else { "ae" };
+    assert_eq!(parse(&format!("{}{}", &ur[..ur.len() - 2], other))["checksumValid"], false);
+    assert_error(ur_parse(c_str(&format!("{}ab", ur))));
+    assert_error(ur_parse(c_str(&format!("{}a", ur))));
+
+    let psbt = vec![0x70; 1000];
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()));
+    let encoder = object(crypto_psbt_get_ur_encoder(crypto_psbt));
+    let part = string(ur_encoder_next_part(encoder));
+    assert_eq!(
+        parse(&part),
+        serde_json::json!({"scheme": "ur", "type": "crypto-psbt", "multipart": true, "seq": 1, "seqLen": 3, "checksumValid": true})
+    );
+
+    assert_error(ur_parse(c_str("https://example.com")));
+    assert_error(ur_parse(c_str("ur:crypto-psbt/0-3/hdcx")));
+    for ur in malformed_strings() {
+        assert_error(ur_parse(ur));
+    }
+}
+
+#[test]
+fn test_qr_rejects_malformed_input() {
+    for text in [null_mut(), invalid_utf8()] {
+        assert_error(qr_encode_matrix(text, 0, 4));
+        assert_error(qr_encode_svg(text, 0, 4, 4));
+        assert_error(qr_encode_png(text, 0, 4, 4));
+    }
+    assert_error(qr_encode_svg(c_str("ur:bytes/hdcx"), 0, 4, 0));
+    assert_error(qr_encode_png(c_str("ur:bytes/hdcx"), 0, 4, MAX_MODULE_SIZE + 1));
+    assert_error(qr_encode_matrix(c_str("ur:bytes/hdcx"), 4, 4));
+    let text = "ur:bytes/".repeat(60);
+    assert_error(qr_encode_png(c_str(&text), 0, MAX_QUIET_ZONE, MAX_MODULE_SIZE));
+}
+
+#[test]
+fn test_qr_encode_matrix() {
+    let matrix: serde_json::Value = serde_json::from_str(&string(qr_encode_matrix(c_str("ur:bytes/hdcx"), 0, 4))).unwrap();
+    assert_eq!(matrix["width"], 29);
+    let modules = hex::decode(matrix["modules"].as_str().unwrap()).unwrap();
+    assert_eq!(modules.len(), 29 * 29);
+    assert_eq!((modules[3 * 29 + 3], modules[4 * 29 + 4]), (0, 1));
+}
+
+#[test]
+fn test_decoder_receives_camera_frames() {
+    let psbt = vec![0x70; 500];
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()));
+    let encoder = object(crypto_psbt_get_ur_encoder_with_options(crypto_psbt, 200, 0, 0));
+    let decoder = object(ur_decoder_new());
+
+    // a 4 pixel per module code drawn at (30, 20) on a gray frame with padded rows
+    let (width, height, stride) = (400usize, 360usize, 416usize);
+    let mut status = serde_json::Value::Null;
+    while !boolean(ur_decoder_is_complete(decoder)) {
+        let part = string(ur_encoder_next_part(encoder));
+        let (size, pixels) = crate::qr::encode_matrix(&part, 0, 4).unwrap().to_luma(4).unwrap();
+        let mut frame = vec![128u8; stride * height];
+        for y in 0..size {
+            frame[(y + 20) * stride + 30..(y + 20) * stride + 30 + size].copy_from_slice(&pixels[y * size..(y + 1) * size]);
+        }
+        status = serde_json::from_str(&string(ur_decoder_receive_frame(
+            decoder, width as u32, height as u32, stride as u32, frame.as_ptr(), frame.len(),
+        )))
+        .unwrap();
+        assert_eq!(status["found"], 1);
+        assert_eq!(status["received"], 1);
+    }
+    assert_eq!(status["complete"], true);
+    let resolved = object(ur_decoder_resolve_auto(decoder));
+    let response = Response::from_ptr(crypto_psbt_get_data_bytes(resolved));
+    let bytes = response.value.get_bytes();
+    assert_eq!(unsafe { std::slice::from_raw_parts(bytes.data, bytes.len) }, psbt.as_slice());
+
+    let (size, pixels) = crate::qr::encode_matrix("https://example.com", 0, 4).unwrap().to_luma(4).unwrap();
+    let status: serde_json::Value = serde_json::from_str(&string(ur_decoder_receive_frame(
+        object(ur_decoder_new()), size as u32, size as u32, size as u32, pixels.as_ptr(), pixels.len(),
+    )))
+    .unwrap();
+    assert_eq!(status["found"], 1);
+    assert_eq!(status["rejected"][0]["kind"], "InvalidScheme");
+
+    let blank = vec![255u8; 100 * 100];
+    let status: serde_json::Value =
+        serde_json::from_str(&string(ur_decoder_receive_frame(decoder, 100, 100, 100, blank.as_ptr(), blank.len()))).unwrap();
+    assert_eq!(status["found"], 0);
+    assert_error(ur_decoder_receive_frame(decoder, 100, 100, 99, blank.as_ptr(), blank.len()));
+    assert_error(ur_decoder_receive_frame(decoder, 100, 101, 100, blank.as_ptr(), blank.len()));
+    assert_error(ur_decoder_receive_frame(decoder, 0, 100, 100, blank.as_ptr(), blank.len()));
+    assert_error(ur_decoder_receive_frame(decoder, 100, 100, 100, null(), blank.len()));
+    for handle in malformed_handles(crypto_psbt) {
+        assert_error(ur_decoder_receive_frame(handle, 100, 100, 100, blank.as_ptr(), blank.len()));
+    }
+}
+
+#[test]
+fn test_decoder_session_separates_messages() {
+    let session = object(ur_decoder_session_new(60_000));
+    let first = vec![0x70; 1000];
+    let first = object(crypto_psbt_construct_from_bytes(first.as_ptr(), first.len()));
+    let first = object(crypto_psbt_get_ur_encoder(first));
+    let second = vec![0x71; 10];
+    let second = object(crypto_psbt_construct_from_bytes(second.as_ptr(), second.len()));
+    let single = string(ur_encoder_next_part(object(crypto_psbt_get_ur_encoder(second))));
+
+    assert_error(ur_decoder_session_take_completed(session));
+    let status: serde_json::Value =
+        serde_json::from_str(&string(ur_decoder_session_receive(session, c_str(&string(ur_encoder_next_part(first))))))
+            .unwrap();
+    assert_eq!(status, serde_json::json!({"type": "crypto-psbt", "complete": false, "expired": 0}));
+    let status: serde_json::Value =
+        serde_json::from_str(&string(ur_decoder_session_receive(session, c_str(&single)))).unwrap();
+    assert_eq!(status["complete"], true);
+    let status: serde_json::Value =
+        serde_json::from_str(&string(ur_decoder_session_receive(session, c_str(&single)))).unwrap();
+    assert_eq!(status["complete"], false);
+
+    let pending: serde_json::Value = serde_json::from_str(&string(ur_decoder_session_pending(session))).unwrap();
+    assert_eq!(pending.as_array().unwrap().len(), 1);
+    assert_eq!(pending[0]["type"], "crypto-psbt");
+    assert_eq!(uint32(ur_decoder_session_completed_count(session)), 1);
+
+    let decoder = object(ur_decoder_session_take_completed(session));
+    assert_eq!(string(ur_decoder_result(decoder)), format!("4a{}", "71".repeat(10)));
+    assert_eq!(uint32(ur_decoder_session_completed_count(session)), 0);
+    assert_eq!(Response::from_ptr(ur_decoder_free(decoder)).status_code, SUCCESS);
+
+    loop {
+        let part = string(ur_encoder_next_part(first));
+        let status: serde_json::Value =
+            serde_json::from_str(&string(ur_decoder_session_receive(session, c_str(&part)))).unwrap();
+        if status["complete"] == true {
+            break;
+        }
+    }
+    let decoder = object(ur_decoder_session_take_completed(session));
+    assert_eq!(uint32(ur_decoder_processed_parts_count(decoder)), uint32(ur_decoder_expected_part_count(decoder)));
+    assert_eq!(string(ur_decoder_session_pending(session)), "[]");
+
+    let kind = |ur: &str| Response::from_ptr(ur_decoder_session_receive(session, c_str(ur))).status_code;
+    assert_eq!(kind("crypto-psbt/1-3/zzzz"), ErrorKind::InvalidScheme as u32);
+    assert_eq!(kind("ur:crypto-psbt/1-3/zzzz"), ErrorKind::InvalidBytewords as u32);
+    for ur in malformed_strings() {
+        assert_error(ur_decoder_session_receive(session, ur));
+    }
+    assert_eq!(Response::from_ptr(ur_decoder_session_reset(session)).status_code, SUCCESS);
+
+    let getters: Vec<extern "C" fn(PtrVoid) -> PtrResponse> = vec![
+        ur_decoder_session_pending,
+        ur_decoder_session_completed_count,
+        ur_decoder_session_take_completed,
+        ur_decoder_session_reset,
+        ur_decoder_session_free,
+    ];
+    for getter in getters {
+        for handle in malformed_handles(decoder) {
+            assert_error(getter(handle));
+        }
+    }
+    for handle in malformed_handles(decoder) {
+        assert_error(ur_decoder_session_receive(handle, c_str(&single)));
+    }
+    assert_eq!(Response::from_ptr(ur_decoder_session_free(session)).status_code, SUCCESS);
+}
+
+#[test]
+fn test_decoder_resumes_from_serialized_state() {
+    let psbt: Vec<u8> = (0..20_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()));
+    let encoder = object(crypto_psbt_get_ur_encoder_with_options(crypto_psbt, 200, 0, 0));
+    let fragment_count = uint32(ur_encoder_fragment_count(encoder));
+    assert!(fragment_count >= 100);
+    // skip some parts so that the saved state holds mixed parts as well
+    let parts: Vec<String> = (0..fragment_count * 3)
+        .map(|_| string(ur_encoder_next_part(encoder)))
+        .enumerate()
+        .filter(|(i, _)| i % 3 != 1)
+        .map(|(_, part)| part)
+        .collect();
+
+    let uninterrupted = object(ur_decoder_new());
+    let mut interrupted = object(ur_decoder_new());
+    let half = (fragment_count / 2) as usize;
+    let mut restored_at = None;
+    for (i, part) in parts.iter().enumerate() {
+        if !boolean(ur_decoder_is_complete(uninterrupted)) {
+            assert_eq!(Response::from_ptr(ur_decoder_receive(uninterrupted, c_str(part))).status_code, SUCCESS);
+        }
+        if boolean(ur_decoder_is_complete(interrupted)) {
+            continue;
+        }
+        if i == half {
+            let state = string(ur_decoder_serialize(interrupted));
+            let processed = uint32(ur_decoder_processed_parts_count(interrupted));
+            assert_eq!(Response::from_ptr(ur_decoder_free(interrupted)).status_code, SUCCESS);
+            interrupted = object(ur_decoder_deserialize(c_str(&state)));
+            assert_eq!(uint32(ur_decoder_processed_parts_count(interrupted)), processed);
+            assert_eq!(string(ur_decoder_get_type(interrupted)), "crypto-psbt");
+            restored_at = Some(i);
+        }
+        assert_eq!(Response::from_ptr(ur_decoder_receive(interrupted, c_str(part))).status_code, SUCCESS);
+    }
+    assert_eq!(restored_at, Some(half));
+    assert!(boolean(ur_decoder_is_complete(uninterrupted)));
+    assert!(boolean(ur_decoder_is_complete(interrupted)));
+    assert_eq!(string(ur_decoder_result(interrupted)), string(ur_decoder_result(uninterrupted)));
+    assert_eq!(
+        uint32(ur_decoder_processed_parts_count(interrupted)),
+        uint32(ur_decoder_processed_parts_count(uninterrupted))
+    );
+
+    let state = string(ur_decoder_serialize(interrupted));
+    let restored = object(ur_decoder_deserialize(c_str(&state)));
+    assert!(boolean(ur_decoder_is_complete(restored)));
+
+    let empty = object(ur_decoder_deserialize(c_str(&string(ur_decoder_serialize(object(ur_decoder_new()))))));
+    assert_eq!(uint32(ur_decoder_processed_parts_count(empty)), 0);
+
+    for state in malformed_strings() {
+        assert_error(ur_decoder_deserialize(state));
+    }
+    assert_error(ur_decoder_deserialize(c_str(r#"{"version": 2, "processedPartsCount": 0, "parts": []}"#)));
+    let response = Response::from_ptr(ur_decoder_deserialize(c_str(
+        r#"{"version": 1, "processedPartsCount": 1, "parts": ["ur:crypto-psbt/1-3/zzzz"]}"#,
+    )));
+    assert_eq!(response.status_code, ErrorKind::InvalidBytewords as u32);
+    for handle in malformed_handles(crypto_psbt) {
+        assert_error(ur_decoder_serialize(handle));
+    }
+}
+
+fn crafted_part(seq: u32, seq_len: u32, message_len: u64, fragment: Vec<u8>) -> String {
+    let part = (seq, seq_len, message_len, 0x12345678u32, serde_cbor::Value::Bytes(fragment));
+    format!("ur:bytes/{}-{}/{}", seq, seq_len, encode_payload(&serde_cbor::to_vec(&part).unwrap()))
+}
+
+#[test]
+fn test_decoder_session_limits() {
+    let session = object(ur_decoder_session_new_with_limits(0, 1, 100, 0, 0));
+    let psbt = |byte: u8, len: usize| {
+        let psbt = vec![byte; len];
+        object(crypto_psbt_get_ur_encoder(object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()))))
+    };
+    let part = string(ur_encoder_next_part(psbt(0x70, 1000)));
+    let response = Response::from_ptr(ur_decoder_session_receive(session, c_str(&part)));
+    assert_eq!(response.status_code, ErrorKind::LimitExceeded as u32);
+
+    let session = object(ur_decoder_session_new_with_limits(0, 1, 0, 0, 0));
+    let receive = |encoder| -> serde_json::Value {
+        let part = string(ur_encoder_next_part(encoder));
+        serde_json::from_str(&string(ur_decoder_session_receive(session, c_str(&part)))).unwrap()
+    };
+    assert_eq!(receive(psbt(0x70, 1000))["expired"], 0);
+    assert_eq!(receive(psbt(0x71, 1000))["expired"], 1);
+    let pending: serde_json::Value = serde_json::from_str(&string(ur_decoder_session_pending(session))).unwrap();
+    assert_eq!(pending.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_decoder_limits_reject_crafted_parts() {
+    let kind = |decoder: PtrVoid, ur: &str| Response::from_ptr(ur_decoder_receive(decoder, c_str(ur))).status_code;
+
+    let decoder = object(ur_decoder_new());
+    assert_eq!(kind(decoder, &crafted_part(1, u32::MAX, 100, vec![0; 10])), ErrorKind::LimitExceeded as u32);
+    assert_eq!(kind(decoder, &crafted_part(1, 2, u64::MAX, vec![0; 10])), ErrorKind::LimitExceeded as u32);
+    assert_eq!(uint32(ur_decoder_processed_parts_count(decoder)), 0);
+    assert_error(ur_decoder_get_type(decoder));
+
+    let decoder = object(ur_decoder_new_with_limits(1000, 10, 0));
+    assert_eq!(kind(decoder, &crafted_part(1, 11, 110, vec![0; 10])), ErrorKind::LimitExceeded as u32);
+    assert_eq!(kind(decoder, &crafted_part(1, 2, 1001, vec![0; 501])), ErrorKind::LimitExceeded as u32);
+    let oversized = serde_cbor::to_vec(&serde_cbor::Value::Bytes(vec![0; 1000])).unwrap();
+    assert_eq!(kind(decoder, &format!("ur:bytes/{}", encode_payload(&oversized))), ErrorKind::LimitExceeded as u32);
+
+    // a message of 11 fragments sent as mixed parts only
+    let psbt = vec![0x70; 1000];
+    let crypto_psbt = object(crypto_psbt_construct_from_bytes(psbt.as_ptr(), psbt.len()));
+    let encoder = object(crypto_psbt_get_ur_encoder_with_options(crypto_psbt, 100, 0, 20));
+    let parts: Vec<String> = (0..4).map(|_| string(ur_encoder_next_part(encoder))).collect();
+    let decoder = object(ur_decoder_new_with_limits(0, 0, 3));
+    for part in &parts[..3] {
+        assert_eq!(kind(decoder, part), SUCCESS);
+    }
+    assert_eq!(kind(decoder, &parts[0]), SUCCESS);
+    let response = Response::from_ptr(ur_decoder_receive(decoder, c_str(&parts[3])));
+    assert_eq!(response.status_code, ErrorKind::LimitExceeded as u32);
+    let error = unsafe { CStr::from_ptr(response.error_message) }.to_str().unwrap();
+    assert!(error.contains("\"kind\":\"LimitExceeded\""));
+
+    let restored = object(ur_decoder_deserialize(c_str(&string(ur_decoder_serialize(decoder)))));
+    assert_eq!(kind(restored, &parts[3]), ErrorKind::LimitExceeded as u32);
+    assert_eq!(Response::from_ptr(ur_decoder_reset(decoder)).status_code, SUCCESS);
+    assert_eq!(kind(decoder, &parts[3]), SUCCESS);
+}
+
+#[test]
+fn test_eth_sign_request_getters_after_round_trip() {
+    let sign_data = "48656c6c6f";
+    let address = "9858effd232b4033e47d90003d41ec34ecaeda94";
+    let request = object(eth_sign_request_construct(
+        c_str("9b1deb4d3b7d4bad9bdd2b0d7b3dcb6d"),
+        c_str(sign_data),
+        3,
+        137,
+        c_str("m/44'/60'/0'/0/0"),
+        0x73c5da0a,
+        c_str(address),
+        c_str("metamask"),
+    ));
+    let part = string(ur_encoder_next_part(object(eth_sign_request_get_ur_encoder(request))));
+    let decoder = object(ur_decoder_new());
+    assert_eq!(Response::from_ptr(ur_decoder_receive(decoder, c_str(&part))).status_code, SUCCESS);
+    let request = object(ur_decoder_resolve(decoder, c_str("eth-sign-request")));
+
+    assert_eq!(string(eth_sign_request_get_request_id(request)), "9b1deb4d3b7d4bad9bdd2b0d7b3dcb6d");
+    assert_eq!(string(eth_sign_request_get_sign_data(request)), sign_data);
+    let response = Response::from_ptr(eth_sign_request_get_sign_data_bytes(request));
+    let bytes = response.value.get_bytes();
+    assert_eq!(unsafe { std::slice::from_raw_parts(bytes.data, bytes.len) }, b"Hello");
+    assert_eq!(uint32(eth_sign_request_get_data_type(request)), 3);
+    assert_eq!(string(eth_sign_request_get_chain_id(request)), "137");
+    assert_eq!(string(eth_sign_request_get_derivation_path(request)), "44'/60'/0'/0/0");
+    assert_eq!(string(eth_sign_request_get_source_fingerprint(request)), "73c5da0a");
+    assert_eq!(string(eth_sign_request_get_address(request)), address);
+    assert_eq!(string(eth_sign_request_get_origin(request)), "metamask");
+}
+
+#[test]
+fn test_eth_sign_request_get_summary() {
+    let summary = |sign_data: &[u8], sign_type: u32| {
+        let request = object(eth_sign_request_construct(
+            c_str("9b1deb4d3b7d4bad9bdd2b0d7b3dcb6d"),
+            c_str(&hex::encode(sign_data)),
+            sign_type,
+            1,
+            c_str("m/44'/60'/0'/0/0"),
+            0x73c5da0a,
+            c_str(""),
+            c_str(""),
+        ));
+        Response::from_ptr(eth_sign_request_get_summary(request))
+    };
+    let json = |response: Response| -> serde_json::Value {
+        assert_eq!(response.status_code, SUCCESS);
+        let value = unsafe { CStr::from_ptr(response.value.get_string()) };
+        serde_json::from_str(value.to_str().unwrap()).unwrap()
+    };
+
+    // the example of EIP-155
+    let legacy = hex::decode("ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080")
+        .unwrap();
+    let legacy = json(summary(&legacy, 1));
+    assert_eq!(legacy["dataType"], "transaction");
+    assert_eq!(legacy["transactionType"], "legacy");
+    assert_eq!(legacy["chainId"], "1");
+    assert_eq!(legacy["nonce"], "9");
+    assert_eq!(legacy["gasPrice"], "20000000000");
+    assert_eq!(legacy["gasLimit"], "21000");
+    assert_eq!(legacy["to"], "35".repeat(20));
+    assert_eq!(legacy["value"], "1000000000000000000");
+    assert_eq!(legacy["data"], "");
+    assert_eq!(legacy["call"], serde_json::Value::Null);
+
+    let approve = hex::decode(format!("095ea7b3{}{}", "00".repeat(12) + &"22".repeat(20), "ff".repeat(32))).unwrap();
+    let mut fields = rlp::RlpStream::new_list(9);
+    fields.append(&1u64).append(&3u64).append(&1_000_000_000u64).append(&100_000_000_000u64);
+    fields.append(&60_000u64).append(&[0x35u8; 20].as_slice()).append(&0u64).append(&approve);
+    fields.begin_list(0);
+    let mut eip1559 = vec![0x02];
+    eip1559.extend_from_slice(&fields.out());
+    let eip1559 = json(summary(&eip1559, 4));
+    assert_eq!(eip1559["transactionType"], "eip1559");
+    assert_eq!(eip1559["chainId"], "1");
+    assert_eq!(eip1559["nonce"], "3");
+    assert_eq!(eip1559["maxPriorityFeePerGas"], "1000000000");
+    assert_eq!(eip1559["maxFeePerGas"], "100000000000");
+    assert_eq!(eip1559["gasLimit"], "60000");
+    assert_eq!(eip1559["value"], "0");
+    assert_eq!(eip1559["data"], hex::encode(&approve));
+    assert_eq!(eip1559["accessList"], serde_json::json!([]));
+    assert_eq!(eip1559["call"]["function"], "approve");
+    assert_eq!(eip1559["call"]["unlimitedApproval"], true);
+
+    let message = json(summary(b"Hello", 3));
+    assert_eq!(message, serde_json::json!({"dataType": "personalMessage", "utf8": "Hello", "hex": "48656c6c6f"}));
+    let message = json(summary(&[0xc3, 0x28], 3));
+    assert_eq!(message["utf8"], serde_json::Value::Null);
+    assert_eq!(message["hex"], "c328");
+
+    let typed_data = r#"{
+        "types": {
+            "EIP712Domain": [{"name": "name", "type": "string"}, {"name": "chainId", "type": "uint256"}],
+            "Vote": [{"name": "proposal", "type": "uint256"}, {"name": "support", "type": "bool"}]
+        },
+        "primaryType": "Vote",
+        "domain": {"name": "Governor", "chainId": 1},
+        "message": {"proposal": "42", "support": true}
+    }"#;
+    let typed_data = json(summary(typed_data.as_bytes(), 2));
+    assert_eq!(typed_data["dataType"], "typedData");
+    assert_eq!(typed_data["primaryType"], "Vote");
+    assert_eq!(
+        typed_data["domain"]["children"][0],
+        serde_json::json!({"name": "name", "type": "string", "value": "Governor"})
+    );
+    assert_eq!(
+        typed_data["message"],
+        serde_json::json!({"name": "message", "type": "Vote", "children": [
+            {"name": "proposal", "type": "uint256", "value": "42"},
+            {"name": "support", "type": "bool", "value": true},
+        ]})
+    );
+
+    assert_eq!(summary(&[0xc2, 0x01, 0x02], 1).status_code, ErrorKind::DecodeFailure as u32);
+    assert_eq!(summary(&[0x03, 0xc0], 4).status_code, ErrorKind::UnsupportedType as u32);
+    assert_eq!(summary(b"{}", 2).status_code, ErrorKind::InvalidArgument as u32);
+    for handle in malformed_handles(object(ur_decoder_new())) {
+        assert_error(eth_sign_request_get_summary(handle));
+    }
+}
+
+#[test]
+fn test_eth_signature_recover_signer() {
+    use secp256k1::{Message, Secp256k1, SecretKey};
+    use ur_registry::ethereum::eth_signature::EthSignature;
+
+    let secret_key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let public_key = secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+    let address = hex::encode(crate::ethereum::eth_signer::address(&public_key));
+    let request = |address: &str| {
+        object(eth_sign_request_construct(
+            c_str("9b1deb4d3b7d4bad9bdd2b0d7b3dcb6d"),
+            c_str("48656c6c6f"),
+            3,
+            1,
+            c_str("m/44'/60'/0'/0/0"),
+            0x73c5da0a,
+            c_str(address),
+            c_str(""),
+        ))
+    };
+    let hash = signing_hash(&ur_registry::ethereum::eth_sign_request::DataType::PersonalMessage, b"Hello").unwrap();
+    let (recovery_id, compact) = Secp256k1::new()
+        .sign_ecdsa_recoverable(&Message::from_slice(&hash).unwrap(), &secret_key)
+        .serialize_compact();
+    let mut signature = compact.to_vec();
+    signature.push(27 + recovery_id.to_i32() as u8);
+    let request_id = hex::decode("9b1deb4d3b7d4bad9bdd2b0d7b3dcb6d").unwrap();
+    let eth_signature = crate::handle::register(EthSignature::new(Some(request_id), signature.clone(), None));
+
+    let result: serde_json::Value =
+        serde_json::from_str(&string(eth_signature_recover_signer(eth_signature, request(&address)))).unwrap();
+    assert_eq!(result["hash"], hex::encode(hash));
+    assert_eq!(result["address"], address);
+    assert_eq!(result["publicKey"], hex::encode(public_key.serialize_uncompressed()));
+    assert_eq!(result["addressMatches"], true);
+
+    let result: serde_json::Value = serde_json::from_str(&string(eth_signature_recover_signer(
+        eth_signature,
+        request("0000000000000000000000000000000000000000"),
+    )))
+    .unwrap();
+    assert_eq!(result["addressMatches"], false);
+
+    // an EIP-155 `v` for the chain of the request, then for Goerli
+    let eip155 = |chain_id: u64| {
+        let mut signature = compact.to_vec();
+        signature.push((chain_id * 2 + 35 + recovery_id.to_i32() as u64) as u8);
+        crate::handle::register(EthSignature::new(None, signature, None))
+    };
+    let result: serde_json::Value =
+        serde_json::from_str(&string(eth_signature_recover_signer(eip155(1), request(&address)))).unwrap();
+    assert_eq!(result["address"], address);
+    let response = Response::from_ptr(eth_signature_recover_signer(eip155(5), request(&address)));
+    assert_eq!(response.status_code, ErrorKind::InvalidArgument as u32);
+
+    let other = crate::handle::register(EthSignature::new(Some(vec![0; 16]), signature, None));
+    assert_error(eth_signature_recover_signer(other, request(&address)));
+    let truncated = crate::handle::register(EthSignature::new(None, vec![0; 64], None));
+    assert_error(eth_signature_recover_signer(truncated, request(&address)));
+    for handle in malformed_handles(request(&address)) {
+        assert_error(eth_signature_recover_signer(handle, request(&address)));
+    }
+    for handle in malformed_handles(eth_signature) {
+        assert_error(eth_signature_recover_signer(eth_signature, handle));
+    }
+}
+
+#[test]
+fn test_eth_signature_build_signed_transaction() {
+    use secp256k1::{Message, Secp256k1, SecretKey};
+    use ur_registry::ethereum::eth_signature::EthSignature;
+
+    // signed with the private key 0x4646..46 of EIP-155, the raw transactions match other wallets
+    let secret_key = SecretKey::from_slice(&[0x46; 32]).unwrap();
+    let sign = |sign_data: &[u8], sign_type: u32, chain_id: u32| {
+        let request = object(eth_sign_request_construct(
+            c_str("9b1deb4d3b7d4bad9bdd2b0d7b3dcb6d"),
+            c_str(&hex::encode(sign_data)),
+            sign_type,
+            chain_id,
+            c_str("m/44'/60'/0'/0/0"),
+            0x73c5da0a,
+            c_str(""),
+            c_str(""),
+        ));
+        let hash = crate::ethereum::keccak256(sign_data);
+        let (recovery_id, compact) = Secp256k1::new()
+            .sign_ecdsa_recoverable(&Message::from_slice(&hash).unwrap(), &secret_key)
+            .serialize_compact();
+        let mut signature = compact.to_vec();
+        signature.push(recovery_id.to_i32() as u8);
+        let request_id = hex::decode("9b1deb4d3b7d4bad9bdd2b0d7b3dcb6d").unwrap();
+        let signature = crate::handle::register(EthSignature::new(Some(request_id), signature, None));
+        (signature, request)
+    };
+    let build = |sign_data: &str, sign_type: u32| -> serde_json::Value {
+        let (signature, request) = sign(&hex::decode(sign_data).unwrap(), sign_type, 1);
+        serde_json::from_str(&string(eth_signature_build_signed_transaction(signature, request))).unwrap()
+    };
+
+    let legacy = build(
+        "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080",
+        1,
+    );
+    assert_eq!(
+        legacy["rawTransaction"],
+        "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340b\
+         d939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc6\
+         4214b297fb1966a3b6d83"
+    );
+    assert_eq!(legacy["hash"], "33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788");
+
+    let access_list = build(
+        "01f85e01038504a817c80082753094353535353535353535353535353535353535353501821234f838f794353535353535353535\
+         3535353535353535353535e1a00000000000000000000000000000000000000000000000000000000000000000",
+        4,
+    );
+    assert_eq!(
+        access_list["rawTransaction"],
+        "01f8a101038504a817c80082753094353535353535353535353535353535353535353501821234f838f794353535353535353535\
+         3535353535353535353535e1a0000000000000000000000000000000000000000000000000000000000000000001a07cf0132e\
+         da8d53946ca14522e4e1731f32c3c4eaa3716a834c8712e64e10b6eea078bb7bc71ffe139d5dc19e45a44c325fd3d26c3b3dc1\
+         5ffd13b47af298a0045e"
+    );
+    assert_eq!(access_list["hash"], "601f3283003dbb8238a5306642f9e9ee4bab031e88fa2b6c932ab7865b14a756");
+
+    let eip1559 = build("02e80103843b9aca0085174876e8008252089435353535353535353535353535353535353535350180c0", 4);
+    assert_eq!(
+        eip1559["rawTransaction"],
+        "02f86b0103843b9aca0085174876e8008252089435353535353535353535353535353535353535350180c080a08e22562c6bec6b\
+         0204b16892414b8bbf4d5dd9c52c702c43ea09353f45e01ae6a05762ad1e56ddaf2bd5e9cdb61577ca7e4369778cf7bf1509513\
+         2e8fbab338610"
+    );
+    assert_eq!(eip1559["hash"], "a39bc781d16d45c18c8bd126477b3c8c58a30df21171064df3c4b47a0307288e");
+
+    // the legacy transaction is signed for chain 1, a request for chain 5 does not match it
+    let sign_data =
+        hex::decode("ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080").unwrap();
+    let (signature, request) = sign(&sign_data, 1, 5);
+    let response = Response::from_ptr(eth_signature_build_signed_transaction(signature, request));
+    assert_eq!(response.status_code, ErrorKind::InvalidArgument as u32);
+    let (signature, request) = sign(b"Hello", 3, 1);
+    assert_error(eth_signature_build_signed_transaction(signature, request));
+    for handle in malformed_handles(request) {
+        assert_error(eth_signature_build_signed_transaction(handle, request));
+    }
+    for handle in malformed_handles(signature) {
+        assert_error(eth_signature_build_signed_transaction(signature, handle));
+    }
+}
+
+#[test]
+fn test_eth_abi_decodes_registered_calldata() {
+    let approve = format!("095ea7b3{}{}", "00".repeat(12) + &"22".repeat(20), "ff".repeat(32));
+    let call: serde_json::Value = serde_json::from_str(&string(eth_abi_decode_calldata(c_str(&approve)))).unwrap();
+    assert_eq!(call["function"], "approve");
+    assert_eq!(call["unlimitedApproval"], true);
+
+    let deposit = "d0e30db0";
+    assert_error(eth_abi_decode_calldata(c_str(deposit)));
+    let abi = r#"[{"type": "function", "name": "deposit", "stateMutability": "payable", "inputs": [], "outputs": []}]"#;
+    assert_eq!(uint32(eth_abi_register(c_str(abi))), 1);
+    let call: serde_json::Value = serde_json::from_str(&string(eth_abi_decode_calldata(c_str(deposit)))).unwrap();
+    assert_eq!(call["signature"], "deposit()");
+
+    // sign_szabo_bytecode(bytes16,uint128) has the selector of approve(address,uint256)
+    let spoof = r#"[{"type": "function", "name": "sign_szabo_bytecode", "stateMutability": "nonpayable", "outputs": [],
+        "inputs": [{"name": "a", "type": "bytes16"}, {"name": "b", "type": "uint128"}]}]"#;
+    let response = Response::from_ptr(eth_abi_register(c_str(spoof)));
+    assert_eq!(response.status_code, ErrorKind::InvalidArgument as u32);
+    let call: serde_json::Value = serde_json::from_str(&string(eth_abi_decode_calldata(c_str(&approve)))).unwrap();
+    assert_eq!(call["function"], "approve");
+
+    assert_error(eth_abi_register(c_str("[{")));
+    assert_error(eth_abi_decode_calldata(c_str("zz")));
+    for s in malformed_strings() {
+        assert_error(eth_abi_register(s));
+    }
+    for s in malformed_strings() {
+        assert_error(eth_abi_decode_calldata(s));
+    }
+}
+
+#[test]
+fn test_eth_typed_data_hash() {
+    let mail = MAIL_TYPED_DATA;
+    let hashes: serde_json::Value = serde_json::from_str(&string(eth_typed_data_hash(c_str(mail)))).unwrap();
+    assert_eq!(hashes["domainSeparator"], "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f");
+    assert_eq!(hashes["messageHash"], "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e");
+    assert_eq!(hashes["digest"], "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2");
+
+    let wallet = r#"{"name": "wallet", "type": "address"}"#;
+    let chain_id = r#"{"name": "chainId", "type": "uint256"}"#;
+    for invalid in [
+        // Person -> Mail -> Person
+        mail.replace(wallet, r#"{"name": "wallet", "type": "Mail"}"#),
+        mail.replace(r#""name": "contents""#, r#""name": "contents!""#),
+        mail.replace(chain_id, r#"{"name": "chainId", "type": "uint"}"#),
+    ] {
+        let response = Response::from_ptr(eth_typed_data_hash(c_str(&invalid)));
+        assert_eq!(response.status_code, ErrorKind::InvalidArgument as u32);
+    }
+    assert_error(eth_typed_data_hash(c_str("{}")));
+    for s in malformed_strings() {
+        assert_error(eth_typed_data_hash(s));
+    }
+}
@@ -1,55 +1,87 @@
-use crate::{response::{PtrResponse, Response}, types::{PtrString, PtrVoid}};
-use serde_json::json;
+use crate::{error::{Error, ErrorKind}, handle::{free, with_object, RegistryObject}, response::{catch_panic, PtrResponse, Response}, types::{PtrString, PtrVoid}};
 use uuid::Uuid;
 use crate::utils::{convert_ptr_string_to_string, remove_prefix_0x};
 use ur_registry::cardano::cardano_catalyst_signature::CardanoCatalystSignature;
 
+impl RegistryObject for CardanoCatalystSignature {
+    fn object_type() -> &'static str {
+        "cardano-catalyst-signature"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match CardanoCatalystSignature::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
   }
 
 #[no_mangle]
 pub extern "C" fn cardano_catalyst_signature_new() -> PtrResponse {
-    Response::success_object(Box::into_raw(Box::new(CardanoCatalystSignature::default())) as PtrVoid).c_ptr()
-}   
+    catch_panic(|| {
+        Response::success_object(CardanoCatalystSignature::default()).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_catalyst_signature_free(catalyst_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CardanoCatalystSignature>(catalyst_signature).c_ptr()
+    })
+}
 
 #[no_mangle]
 pub extern "C" fn cardano_catalyst_signature_construct(
     request_id: PtrString,
     signature: PtrString,
 ) -> PtrResponse {
-    let request_id = convert_ptr_string_to_string(request_id).unwrap();
-    let request_id = match Uuid::parse_str(&request_id) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "uuid is invalid"}).to_string()).c_ptr(),
-    };
-
-    let signature = convert_ptr_string_to_string(signature).unwrap();
-    let signature_bytes = match hex::decode(remove_prefix_0x(&signature)) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "signature is invalid"}).to_string()).c_ptr(),
-    };
-
-    let signature = CardanoCatalystSignature::new(
-        Some(request_id.as_bytes().to_vec()),
-        signature_bytes,
-    );
-
-    Response::success_object(Box::into_raw(Box::new(signature)) as PtrVoid).c_ptr()
+    catch_panic(|| {
+        let request_id = match convert_ptr_string_to_string(request_id) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("request_id")).c_ptr(),
+        };
+        let request_id = match Uuid::parse_str(&request_id) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_argument("request_id", "uuid is invalid")).c_ptr(),
+        };
+
+        let signature = match convert_ptr_string_to_string(signature) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("signature")).c_ptr(),
+        };
+        let signature_bytes = match hex::decode(remove_prefix_0x(&signature)) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_hex("signature", "signature is invalid")).c_ptr(),
+        };
+
+        let signature = CardanoCatalystSignature::new(
+            Some(request_id.as_bytes().to_vec()),
+            signature_bytes,
+        );
+
+        Response::success_object(signature).c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_catalyst_signature_get_request_id(catalyst_signature: &mut CardanoCatalystSignature) -> PtrResponse {
-  match catalyst_signature.get_request_id() {
-    Some(v) => Response::success_string(hex::encode(v)).c_ptr(),
-    None => Response::error(format!("No request id supplied")).c_ptr()
-  }
+pub extern "C" fn cardano_catalyst_signature_get_request_id(catalyst_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(catalyst_signature, |catalyst_signature: &mut CardanoCatalystSignature| {
+            match catalyst_signature.get_request_id() {
+                Some(v) => Response::success_string(hex::encode(v)),
+                None => Response::error(Error::new(ErrorKind::NotFound, "No request id supplied"))
+            }
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_catalyst_signature_get_signature(catalyst_signature: &mut CardanoCatalystSignature) -> PtrResponse {
-    Response::success_string(hex::encode(catalyst_signature.get_signature())).c_ptr()
+pub extern "C" fn cardano_catalyst_signature_get_signature(catalyst_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(catalyst_signature, |catalyst_signature: &mut CardanoCatalystSignature| {
+            Response::success_string(hex::encode(catalyst_signature.get_signature()))
+        })
+        .c_ptr()
+    })
 }
@@ -1,19 +1,32 @@
-use crate::response::{PtrResponse, Response};
+use crate::error::Error;
+use crate::handle::{free, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
 use crate::types::{PtrString, PtrVoid};
 use crate::utils::{convert_ptr_string_to_string, remove_prefix_0x};
 use serde::Deserialize;
-use serde_json::json;
 use ur_registry::cardano::cardano_catalyst_voting_registration::CardanoCatalystVotingRegistrationRequest;
 use ur_registry::cardano::cardano_delegation::CardanoDelegation;
 use ur_registry::crypto_key_path::CryptoKeyPath;
 use uuid::Uuid;
 
+impl RegistryObject for CardanoCatalystVotingRegistrationRequest {
+    fn object_type() -> &'static str {
+        "cardano-catalyst-voting-registration"
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn cardano_catalyst_voting_registration_new() -> PtrResponse {
-    Response::success_object(Box::into_raw(Box::new(
-        CardanoCatalystVotingRegistrationRequest::default(),
-    )) as PtrVoid)
-    .c_ptr()
+    catch_panic(|| {
+        Response::success_object(CardanoCatalystVotingRegistrationRequest::default()).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_catalyst_voting_registration_free(request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CardanoCatalystVotingRegistrationRequest>(request).c_ptr()
+    })
 }
 
 #[derive(Deserialize)]
@@ -45,92 +58,125 @@ pub extern "C" fn cardano_catalyst_voting_registration_construct(
     origin: PtrString,
     sign_type: u8,
 ) -> PtrResponse {
-    let request_id = convert_ptr_string_to_string(request_id).unwrap();
-    let request_id = match Uuid::parse_str(&request_id) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "uuid is invalid"}).to_string()).c_ptr(),
-    }
-    .as_bytes()
-    .to_vec();
+    catch_panic(|| {
+        let request_id = match convert_ptr_string_to_string(request_id) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("request_id")).c_ptr(),
+        };
+        let request_id = match Uuid::parse_str(&request_id) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_argument("request_id", "uuid is invalid")).c_ptr(),
+        }
+        .as_bytes()
+        .to_vec();
 
-    let mfp = convert_ptr_string_to_string(mfp).unwrap();
-    let mfp = match hex::decode(remove_prefix_0x(&mfp)) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "mfp is invalid"}).to_string()).c_ptr(),
-    };
+        let mfp = match convert_ptr_string_to_string(mfp) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("mfp")).c_ptr(),
+        };
+        let mfp = match hex::decode(remove_prefix_0x(&mfp)) {
+            Ok(v) => v,
+            Err(e) => return Response::error(Error::invalid_hex("mfp", e)).c_ptr(),
+        };
 
-    if mfp.len() != 4 {
-        return Response::error(json!({"error": "mfp is invalid"}).to_string()).c_ptr();
-    }
+        if mfp.len() != 4 {
+            return Response::error(Error::invalid_argument("mfp", "mfp is invalid")).c_ptr();
+        }
 
-    let delegations = convert_ptr_string_to_string(delegations).unwrap();
-    let delegations: Vec<CardanoDelegation> = serde_json::from_str::<Vec<Delegation>>(&delegations)
-        .unwrap()
-        .into_iter()
-        .map(|d| {
-            CardanoDelegation::new(
+        let delegations = match convert_ptr_string_to_string(delegations) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("delegations")).c_ptr(),
+        };
+        let delegations = match serde_json::from_str::<Vec<Delegation>>(&delegations) {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_argument("delegations", "delegations is invalid")).c_ptr()
+            }
+        };
+        let delegations: Vec<CardanoDelegation> = match delegations
+            .into_iter()
+            .map(|d| {
                 hex::decode(remove_prefix_0x(&d.pub_key))
-                    .unwrap()
-                    .as_slice()
-                    .to_vec(),
-                d.width,
-            )
-        })
-        .collect();
-
-    let stake_pub = convert_ptr_string_to_string(stake_pub).unwrap();
-    let stake_pub_bytes = match hex::decode(remove_prefix_0x(&stake_pub)) {
-        Ok(v) => v,
-        Err(_) => {
-            return Response::error(json!({"error": "stake pub is invalid"}).to_string()).c_ptr()
-        }
-    };
-
-    let payment_address = convert_ptr_string_to_string(payment_address).unwrap();
-    let payment_address_bytes = match hex::decode(remove_prefix_0x(&payment_address)) {
-        Ok(v) => v,
-        Err(_) => {
-            return Response::error(json!({"error": "payment address is invalid"}).to_string())
-                .c_ptr()
-        }
-    };
-
-    let nonce = convert_ptr_string_to_string(nonce).unwrap();
-    let nonce = match nonce.parse::<u64>() {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "nonce is invalid"}).to_string()).c_ptr(),
-    };
-
-    let derivation_path = convert_ptr_string_to_string(derivation_path).unwrap();
-    let derivation_path = match CryptoKeyPath::from_path(
-        derivation_path,
-        Some(mfp.as_slice().try_into().ok().unwrap()),
-    ) {
-        Ok(v) => v,
-        Err(_) => {
-            return Response::error(json!({"error": "derivation path is invalid"}).to_string())
-                .c_ptr()
-        }
-    };
-
-    let origin = convert_ptr_string_to_string(origin).unwrap();
-    let origin = if origin.len() == 0 {
-        None
-    } else {
-        Some(origin.to_string())
-    };
-
-    let request = CardanoCatalystVotingRegistrationRequest::new(
-        Some(request_id),
-        delegations,
-        stake_pub_bytes,
-        payment_address_bytes,
-        nonce,
-        voting_purpose,
-        derivation_path,
-        origin,
-        sign_type,
-    );
-
-    Response::success_object(Box::into_raw(Box::new(request)) as PtrVoid).c_ptr()
+                    .map(|pub_key| CardanoDelegation::new(pub_key, d.width))
+            })
+            .collect()
+        {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_hex("delegations", "delegation pub key is invalid"))
+                    .c_ptr()
+            }
+        };
+
+        let stake_pub = match convert_ptr_string_to_string(stake_pub) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("stake_pub")).c_ptr(),
+        };
+        let stake_pub_bytes = match hex::decode(remove_prefix_0x(&stake_pub)) {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_hex("stake_pub", "stake pub is invalid")).c_ptr()
+            }
+        };
+
+        let payment_address = match convert_ptr_string_to_string(payment_address) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("payment_address")).c_ptr(),
+        };
+        let payment_address_bytes = match hex::decode(remove_prefix_0x(&payment_address)) {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_hex("payment_address", "payment address is invalid"))
+                    .c_ptr()
+            }
+        };
+
+        let nonce = match convert_ptr_string_to_string(nonce) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("nonce")).c_ptr(),
+        };
+        let nonce = match nonce.parse::<u64>() {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_argument("nonce", "nonce is invalid")).c_ptr(),
+        };
+
+        let derivation_path = match convert_ptr_string_to_string(derivation_path) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("derivation_path")).c_ptr(),
+        };
+        let derivation_path = match CryptoKeyPath::from_path(
+            derivation_path,
+            Some(mfp.as_slice().try_into().ok().unwrap()),
+        ) {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_path("derivation_path", "derivation path is invalid"))
+                    .c_ptr()
+            }
+        };
+
+        let origin = match convert_ptr_string_to_string(origin) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("origin")).c_ptr(),
+        };
+        let origin = if origin.len() == 0 {
+            None
+        } else {
+            Some(origin.to_string())
+        };
+
+        let request = CardanoCatalystVotingRegistrationRequest::new(
+            Some(request_id),
+            delegations,
+            stake_pub_bytes,
+            payment_address_bytes,
+            nonce,
+            voting_purpose,
+            derivation_path,
+            origin,
+            sign_type,
+        );
+
+        Response::success_object(request).c_ptr()
+    })
 }
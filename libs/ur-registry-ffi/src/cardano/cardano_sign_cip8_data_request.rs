@@ -1,20 +1,33 @@
-use crate::response::{PtrResponse, Response};
+use crate::error::Error;
+use crate::handle::{free, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
 use crate::types::{PtrString, PtrVoid};
 use crate::utils::convert_ptr_string_to_string;
 use crate::utils::remove_prefix_0x;
-use serde_json::json;
 use ur_registry::cardano::cardano_sign_cip8_data_request::{
     CardanoSignCip8DataRequest, Cip8AddressType,
 };
 use ur_registry::crypto_key_path::CryptoKeyPath;
 use uuid::Uuid;
 
+impl RegistryObject for CardanoSignCip8DataRequest {
+    fn object_type() -> &'static str {
+        "cardano-sign-cip8-data-request"
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn cardano_sign_cip8_data_request_new() -> PtrResponse {
-    Response::success_object(
-        Box::into_raw(Box::new(CardanoSignCip8DataRequest::default())) as PtrVoid,
-    )
-    .c_ptr()
+    catch_panic(|| {
+        Response::success_object(CardanoSignCip8DataRequest::default()).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_sign_cip8_data_request_free(request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CardanoSignCip8DataRequest>(request).c_ptr()
+    })
 }
 
 // request_id: Option<Bytes>,
@@ -38,82 +51,105 @@ pub extern "C" fn cardano_sign_cip8_data_request_construct(
     address_bench32: PtrString,
     address_type: u32,
 ) -> PtrResponse {
-    let mfp = convert_ptr_string_to_string(mfp).unwrap();
-    let mfp = match hex::decode(remove_prefix_0x(&mfp)) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "mfp is invalid"}).to_string()).c_ptr(),
-    };
+    catch_panic(|| {
+        let mfp = match convert_ptr_string_to_string(mfp) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("mfp")).c_ptr(),
+        };
+        let mfp = match hex::decode(remove_prefix_0x(&mfp)) {
+            Ok(v) => v,
+            Err(e) => return Response::error(Error::invalid_hex("mfp", e)).c_ptr(),
+        };
 
-    if mfp.len() != 4 {
-        return Response::error(json!({"error": "mfp is invalid"}).to_string()).c_ptr();
-    }
-
-    let derivation_path = convert_ptr_string_to_string(derivation_path).unwrap();
-    let derivation_path = match CryptoKeyPath::from_path(
-        derivation_path,
-        Some(mfp.as_slice().try_into().ok().unwrap()),
-    ) {
-        Ok(v) => v,
-        Err(_) => {
-            return Response::error(json!({"error": "derivation path is invalid"}).to_string())
-                .c_ptr()
+        if mfp.len() != 4 {
+            return Response::error(Error::invalid_argument("mfp", "mfp is invalid")).c_ptr();
         }
-    };
 
-    let request_id = convert_ptr_string_to_string(request_id).unwrap();
-    let request_id = match Uuid::parse_str(&request_id) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "uuid is invalid"}).to_string()).c_ptr(),
-    }
-    .as_bytes()
-    .to_vec();
+        let derivation_path = match convert_ptr_string_to_string(derivation_path) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("derivation_path")).c_ptr(),
+        };
+        let derivation_path = match CryptoKeyPath::from_path(
+            derivation_path,
+            Some(mfp.as_slice().try_into().ok().unwrap()),
+        ) {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_path("derivation_path", "derivation path is invalid"))
+                    .c_ptr()
+            }
+        };
 
-    let sign_data = convert_ptr_string_to_string(sign_data).unwrap();
-    let sign_data_bytes = match hex::decode(remove_prefix_0x(&sign_data)) {
-        Ok(v) => v,
-        Err(_) => {
-            return Response::error(json!({"error": "sign data is invalid"}).to_string()).c_ptr()
+        let request_id = match convert_ptr_string_to_string(request_id) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("request_id")).c_ptr(),
+        };
+        let request_id = match Uuid::parse_str(&request_id) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_argument("request_id", "uuid is invalid")).c_ptr(),
         }
-    };
+        .as_bytes()
+        .to_vec();
 
-    let origin = convert_ptr_string_to_string(origin).unwrap();
-    let origin = if origin.len() == 0 {
-        None
-    } else {
-        Some(origin.to_string())
-    };
+        let sign_data = match convert_ptr_string_to_string(sign_data) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("sign_data")).c_ptr(),
+        };
+        let sign_data_bytes = match hex::decode(remove_prefix_0x(&sign_data)) {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_hex("sign_data", "sign data is invalid")).c_ptr()
+            }
+        };
 
-    let xpub = convert_ptr_string_to_string(xpub).unwrap();
-    let xpub_bytes = match hex::decode(remove_prefix_0x(&xpub)) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "xpub is invalid"}).to_string()).c_ptr(),
-    };
+        let origin = match convert_ptr_string_to_string(origin) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("origin")).c_ptr(),
+        };
+        let origin = if origin.len() == 0 {
+            None
+        } else {
+            Some(origin.to_string())
+        };
 
-    let address_bench32 = convert_ptr_string_to_string(address_bench32).unwrap();
-    let address_bench32 = if address_bench32.len() == 0 {
-        None
-    } else {
-        Some(address_bench32.to_string())
-    };
+        let xpub = match convert_ptr_string_to_string(xpub) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("xpub")).c_ptr(),
+        };
+        let xpub_bytes = match hex::decode(remove_prefix_0x(&xpub)) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_hex("xpub", "xpub is invalid")).c_ptr(),
+        };
 
-    let address_type = match address_type {
-        0 => Cip8AddressType::Address,
-        1 => Cip8AddressType::KeyHash,
-        _ => {
-            return Response::error(json!({"error": "address type is invalid"}).to_string()).c_ptr()
-        }
-    };
+        let address_bench32 = match convert_ptr_string_to_string(address_bench32) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("address_bench32")).c_ptr(),
+        };
+        let address_bench32 = if address_bench32.len() == 0 {
+            None
+        } else {
+            Some(address_bench32.to_string())
+        };
+
+        let address_type = match address_type {
+            0 => Cip8AddressType::Address,
+            1 => Cip8AddressType::KeyHash,
+            _ => {
+                return Response::error(Error::invalid_argument("address_type", "address type is invalid")).c_ptr()
+            }
+        };
 
-    let request = CardanoSignCip8DataRequest::new(
-        Some(request_id),
-        sign_data_bytes,
-        derivation_path,
-        origin,
-        xpub_bytes,
-        hash_payload,
-        address_bench32,
-        address_type,
-    );
+        let request = CardanoSignCip8DataRequest::new(
+            Some(request_id),
+            sign_data_bytes,
+            derivation_path,
+            origin,
+            xpub_bytes,
+            hash_payload,
+            address_bench32,
+            address_type,
+        );
 
-    Response::success_object(Box::into_raw(Box::new(request)) as PtrVoid).c_ptr()
+        Response::success_object(request).c_ptr()
+    })
 }
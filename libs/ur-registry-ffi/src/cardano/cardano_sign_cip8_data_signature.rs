@@ -1,20 +1,35 @@
-use crate::{response::{PtrResponse, Response}, types::{PtrString, PtrVoid}};
-use serde_json::json;
+use crate::{error::{Error, ErrorKind}, handle::{free, with_object, RegistryObject}, response::{catch_panic, PtrResponse, Response}, types::{PtrString, PtrVoid}};
 use uuid::Uuid;
 use crate::utils::{convert_ptr_string_to_string, remove_prefix_0x};
 use ur_registry::cardano::cardano_sign_cip8_data_signature::CardanoSignCip8DataSignature;
 
+impl RegistryObject for CardanoSignCip8DataSignature {
+    fn object_type() -> &'static str {
+        "cardano-sign-cip8-data-signature"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match CardanoSignCip8DataSignature::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
 }
 
 #[no_mangle]
-    pub extern "C" fn cardano_sign_cip8_data_signature_new() -> PtrResponse {
-    Response::success_object(Box::into_raw(Box::new(CardanoSignCip8DataSignature::default())) as PtrVoid).c_ptr()
+pub extern "C" fn cardano_sign_cip8_data_signature_new() -> PtrResponse {
+    catch_panic(|| {
+        Response::success_object(CardanoSignCip8DataSignature::default()).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_sign_cip8_data_signature_free(signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CardanoSignCip8DataSignature>(signature).c_ptr()
+    })
 }
+
 //request_id: Option<Bytes>,
 // signature: Bytes,
 // public_key: Bytes,
@@ -26,59 +41,93 @@ pub extern "C" fn cardano_sign_cip8_data_signature_construct(
     public_key: PtrString,
     address_field: PtrString,
 ) -> PtrResponse {
-    let request_id = convert_ptr_string_to_string(request_id).unwrap();
-    let request_id = match Uuid::parse_str(&request_id) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "uuid is invalid"}).to_string()).c_ptr(),
-    }.as_bytes().to_vec();
+    catch_panic(|| {
+        let request_id = match convert_ptr_string_to_string(request_id) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("request_id")).c_ptr(),
+        };
+        let request_id = match Uuid::parse_str(&request_id) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_argument("request_id", "uuid is invalid")).c_ptr(),
+        }.as_bytes().to_vec();
     
-    let signature = convert_ptr_string_to_string(signature).unwrap();
-    let signature_bytes = match hex::decode(remove_prefix_0x(&signature)) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "signature is invalid"}).to_string()).c_ptr(),
-    };
+        let signature = match convert_ptr_string_to_string(signature) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("signature")).c_ptr(),
+        };
+        let signature_bytes = match hex::decode(remove_prefix_0x(&signature)) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_hex("signature", "signature is invalid")).c_ptr(),
+        };
 
-    let public_key = convert_ptr_string_to_string(public_key).unwrap();
-    let public_key_bytes = match hex::decode(remove_prefix_0x(&public_key)) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "public key is invalid"}).to_string()).c_ptr(),
-    };
+        let public_key = match convert_ptr_string_to_string(public_key) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("public_key")).c_ptr(),
+        };
+        let public_key_bytes = match hex::decode(remove_prefix_0x(&public_key)) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_hex("public_key", "public key is invalid")).c_ptr(),
+        };
 
-    let address_field = convert_ptr_string_to_string(address_field).unwrap();
-    let address_field_bytes = match hex::decode(remove_prefix_0x(&address_field)) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "address field is invalid"}).to_string()).c_ptr(),
-    };
+        let address_field = match convert_ptr_string_to_string(address_field) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("address_field")).c_ptr(),
+        };
+        let address_field_bytes = match hex::decode(remove_prefix_0x(&address_field)) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_hex("address_field", "address field is invalid")).c_ptr(),
+        };
     
-    let request = CardanoSignCip8DataSignature::new(
-        Some(request_id),
-        signature_bytes,
-        public_key_bytes,
-        address_field_bytes,
-    );
+        let request = CardanoSignCip8DataSignature::new(
+            Some(request_id),
+            signature_bytes,
+            public_key_bytes,
+            address_field_bytes,
+        );
 
-    Response::success_object(Box::into_raw(Box::new(request)) as PtrVoid).c_ptr()
+        Response::success_object(request).c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_sign_cip8_data_signature_get_request_id(signature: &mut CardanoSignCip8DataSignature) -> PtrResponse {
-    match signature.get_request_id() {
-        Some(v) => Response::success_string(hex::encode(v)).c_ptr(),
-        None => Response::error(format!("No request id supplied")).c_ptr()
-    }
+pub extern "C" fn cardano_sign_cip8_data_signature_get_request_id(signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(signature, |signature: &mut CardanoSignCip8DataSignature| {
+            match signature.get_request_id() {
+                Some(v) => Response::success_string(hex::encode(v)),
+                None => Response::error(Error::new(ErrorKind::NotFound, "No request id supplied"))
+            }
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_sign_cip8_data_signature_get_signature(signature: &mut CardanoSignCip8DataSignature) -> PtrResponse {
-    Response::success_string(hex::encode(signature.get_signature())).c_ptr()
+pub extern "C" fn cardano_sign_cip8_data_signature_get_signature(signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(signature, |signature: &mut CardanoSignCip8DataSignature| {
+            Response::success_string(hex::encode(signature.get_signature()))
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_sign_cip8_data_signature_get_public_key(signature: &mut CardanoSignCip8DataSignature) -> PtrResponse {
-    Response::success_string(hex::encode(signature.get_public_key())).c_ptr()
+pub extern "C" fn cardano_sign_cip8_data_signature_get_public_key(signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(signature, |signature: &mut CardanoSignCip8DataSignature| {
+            Response::success_string(hex::encode(signature.get_public_key()))
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_sign_cip8_data_signature_get_address_field(signature: &mut CardanoSignCip8DataSignature) -> PtrResponse {
-    Response::success_string(hex::encode(signature.get_address_field())).c_ptr()
+pub extern "C" fn cardano_sign_cip8_data_signature_get_address_field(signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(signature, |signature: &mut CardanoSignCip8DataSignature| {
+            Response::success_string(hex::encode(signature.get_address_field()))
+        })
+        .c_ptr()
+    })
 }
@@ -1,13 +1,27 @@
-use crate::{response::{PtrResponse, Response}, types::{PtrString, PtrVoid}};
-use serde_json::json;
+use crate::{error::Error, handle::{free, RegistryObject}, response::{catch_panic, PtrResponse, Response}, types::{PtrString, PtrVoid}};
 use uuid::Uuid;
 use crate::utils::{convert_ptr_string_to_string, remove_prefix_0x};
 use ur_registry::cardano::cardano_sign_data_request::CardanoSignDataRequest;
 use ur_registry::crypto_key_path::CryptoKeyPath;
 
+impl RegistryObject for CardanoSignDataRequest {
+    fn object_type() -> &'static str {
+        "cardano-sign-data-request"
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn cardano_sign_data_request_new() -> PtrResponse {
-    Response::success_object(Box::into_raw(Box::new(CardanoSignDataRequest::default())) as PtrVoid).c_ptr()
+    catch_panic(|| {
+        Response::success_object(CardanoSignDataRequest::default()).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_sign_data_request_free(request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CardanoSignDataRequest>(request).c_ptr()
+    })
 }
 
 // request_id: Option<Bytes>,
@@ -25,64 +39,84 @@ pub extern "C" fn cardano_sign_data_request_construct(
     origin: PtrString,
     xpub: PtrString,
 ) -> PtrResponse {
-    let request_id = convert_ptr_string_to_string(request_id).unwrap();
-    let request_id = match Uuid::parse_str(&request_id) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "uuid is invalid"}).to_string()).c_ptr(),
-    }
-    .as_bytes()
-    .to_vec();
-
-    let mfp = convert_ptr_string_to_string(mfp).unwrap();
-    let mfp = match hex::decode(remove_prefix_0x(&mfp)) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "mfp is invalid"}).to_string()).c_ptr(),
-    };
+    catch_panic(|| {
+        let request_id = match convert_ptr_string_to_string(request_id) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("request_id")).c_ptr(),
+        };
+        let request_id = match Uuid::parse_str(&request_id) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_argument("request_id", "uuid is invalid")).c_ptr(),
+        }
+        .as_bytes()
+        .to_vec();
 
-    if mfp.len() != 4 {
-        return Response::error(json!({"error": "mfp is invalid"}).to_string()).c_ptr();
-    }
+        let mfp = match convert_ptr_string_to_string(mfp) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("mfp")).c_ptr(),
+        };
+        let mfp = match hex::decode(remove_prefix_0x(&mfp)) {
+            Ok(v) => v,
+            Err(e) => return Response::error(Error::invalid_hex("mfp", e)).c_ptr(),
+        };
 
-    let sign_data = convert_ptr_string_to_string(sign_data).unwrap();
-    let sign_data_bytes = match hex::decode(remove_prefix_0x(&sign_data)) {
-        Ok(v) => v,
-        Err(_) => {
-            return Response::error(json!({"error": "sign data is invalid"}).to_string()).c_ptr()
+        if mfp.len() != 4 {
+            return Response::error(Error::invalid_argument("mfp", "mfp is invalid")).c_ptr();
         }
-    };
 
-    let derivation_path = convert_ptr_string_to_string(derivation_path).unwrap();
-    let derivation_path = match CryptoKeyPath::from_path(
-        derivation_path,
-        Some(mfp.as_slice().try_into().ok().unwrap()),
-    ) {
-        Ok(v) => v,
-        Err(_) => {
-            return Response::error(json!({"error": "derivation path is invalid"}).to_string())
-                .c_ptr()
-        }
-    };
+        let sign_data = match convert_ptr_string_to_string(sign_data) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("sign_data")).c_ptr(),
+        };
+        let sign_data_bytes = match hex::decode(remove_prefix_0x(&sign_data)) {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_hex("sign_data", "sign data is invalid")).c_ptr()
+            }
+        };
+
+        let derivation_path = match convert_ptr_string_to_string(derivation_path) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("derivation_path")).c_ptr(),
+        };
+        let derivation_path = match CryptoKeyPath::from_path(
+            derivation_path,
+            Some(mfp.as_slice().try_into().ok().unwrap()),
+        ) {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_path("derivation_path", "derivation path is invalid"))
+                    .c_ptr()
+            }
+        };
 
-    let origin = convert_ptr_string_to_string(origin).unwrap();
-    let origin = if origin.len() == 0 {
-        None
-    } else {
-        Some(origin.to_string())
-    };
+        let origin = match convert_ptr_string_to_string(origin) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("origin")).c_ptr(),
+        };
+        let origin = if origin.len() == 0 {
+            None
+        } else {
+            Some(origin.to_string())
+        };
 
-    let xpub = convert_ptr_string_to_string(xpub).unwrap();
-    let xpub_bytes = match hex::decode(remove_prefix_0x(&xpub)) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "xpub is invalid"}).to_string()).c_ptr(),
-    };
+        let xpub = match convert_ptr_string_to_string(xpub) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("xpub")).c_ptr(),
+        };
+        let xpub_bytes = match hex::decode(remove_prefix_0x(&xpub)) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_hex("xpub", "xpub is invalid")).c_ptr(),
+        };
 
-    let request = CardanoSignDataRequest::new(
-        Some(request_id),
-        sign_data_bytes,
-        derivation_path,
-        origin,
-        xpub_bytes,
-    );
+        let request = CardanoSignDataRequest::new(
+            Some(request_id),
+            sign_data_bytes,
+            derivation_path,
+            origin,
+            xpub_bytes,
+        );
 
-    Response::success_object(Box::into_raw(Box::new(request)) as PtrVoid).c_ptr()
+        Response::success_object(request).c_ptr()
+    })
 }
@@ -1,8 +1,10 @@
-use crate::response::{PtrResponse, Response};
+use crate::error::{Error, ErrorKind};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
 use serde::Deserialize;
-use serde_json::json;
-use crate::types::{PtrString, PtrVoid};
-use crate::utils::{convert_ptr_string_to_string, remove_prefix_0x};
+use crate::types::{PtrBytes, PtrString, PtrVoid};
+use crate::ur_encoder::{new_encoder, EncoderOptions};
+use crate::utils::{convert_ptr_string_to_string, parse_ptr_bytes, remove_prefix_0x};
 use ur_registry::crypto_key_path::CryptoKeyPath;
 use ur_registry::cardano::cardano_sign_request::CardanoSignRequest;
 use ur_registry::cardano::cardano_cert_key::CardanoCertKey;
@@ -27,9 +29,24 @@ struct CertKey {
     key_path: String,
 }
 
+impl RegistryObject for CardanoSignRequest {
+    fn object_type() -> &'static str {
+        "cardano-sign-request"
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn cardano_sign_request_new() -> PtrResponse {
-    Response::success_object(Box::into_raw(Box::new(CardanoSignRequest::default())) as PtrVoid).c_ptr()
+    catch_panic(|| {
+        Response::success_object(CardanoSignRequest::default()).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_sign_request_free(cardano_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CardanoSignRequest>(cardano_sign_request).c_ptr()
+    })
 }
 
 #[no_mangle]
@@ -40,11 +57,52 @@ pub extern "C" fn cardano_sign_request_construct(
     cert_keys: PtrString,
     origin: PtrString,
 ) -> PtrResponse {
+    catch_panic(|| {
+        let sign_data = match convert_ptr_string_to_string(sign_data) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("sign_data")).c_ptr(),
+        };
+        let sign_data = match hex::decode(remove_prefix_0x(&sign_data)) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_hex("sign_data", "sign data is invalid")).c_ptr(),
+        };
+        construct(request_id, sign_data, utxos, cert_keys, origin).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_sign_request_construct_from_bytes(
+    request_id: PtrString,
+    sign_data: PtrBytes,
+    sign_data_len: usize,
+    utxos: PtrString,
+    cert_keys: PtrString,
+    origin: PtrString,
+) -> PtrResponse {
+    catch_panic(|| {
+        let sign_data = match parse_ptr_bytes(sign_data, sign_data_len) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("sign_data")).c_ptr(),
+        };
+        construct(request_id, sign_data, utxos, cert_keys, origin).c_ptr()
+    })
+}
+
+fn construct(
+    request_id: PtrString,
+    sign_data: Vec<u8>,
+    utxos: PtrString,
+    cert_keys: PtrString,
+    origin: PtrString,
+) -> Response {
     let mut args_err_msg = "";
-    let utxos = convert_ptr_string_to_string(utxos).unwrap();
+    let utxos = match convert_ptr_string_to_string(utxos) {
+        Ok(v) => v,
+        Err(e) => return Response::error(e.for_field("utxos")),
+    };
     let utxos: Vec<CardanoUTXO> = match serde_json::from_str::<Vec<Utxo>>(&utxos) {
         Ok(v) => v,
-        Err(e) => return Response::error(json!({"error": format!("utxos is invalid: {}", e.to_string())}).to_string()).c_ptr(),
+        Err(e) => return Response::error(Error::invalid_argument("utxos", format!("utxos is invalid: {}", e))),
     }.iter().map(|utxo| {
         let xfp = match hex::decode(utxo.xfp.clone()) {
             Ok(v) => Some(v),
@@ -81,13 +139,16 @@ pub extern "C" fn cardano_sign_request_construct(
         ))
     }).filter_map(|x| x).collect();
     if args_err_msg != "" {
-        return Response::error(json!({"error": args_err_msg}).to_string()).c_ptr();
+        return Response::error(Error::invalid_argument("utxos", args_err_msg));
     }
 
-    let cert_keys = convert_ptr_string_to_string(cert_keys).unwrap();
+    let cert_keys = match convert_ptr_string_to_string(cert_keys) {
+        Ok(v) => v,
+        Err(e) => return Response::error(e.for_field("cert_keys")),
+    };
     let cert_keys: Vec<CardanoCertKey> = match serde_json::from_str::<Vec<CertKey>>(&cert_keys) {
         Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "cert keys is invalid"}).to_string()).c_ptr(),
+        Err(_) => return Response::error(Error::invalid_argument("cert_keys", "cert keys is invalid")),
     }.iter().map(|cert_key| {
         let xfp = match hex::decode(cert_key.xfp.clone()) {
             Ok(v) => Some(v),
@@ -124,51 +185,75 @@ pub extern "C" fn cardano_sign_request_construct(
         ))
     }).filter_map(|x| x).collect();
     if args_err_msg != "" {
-        return Response::error(json!({"error": args_err_msg}).to_string()).c_ptr();
+        return Response::error(Error::invalid_argument("cert_keys", args_err_msg));
     }
 
-    let request_id = convert_ptr_string_to_string(request_id).unwrap();
+    let request_id = match convert_ptr_string_to_string(request_id) {
+        Ok(v) => v,
+        Err(e) => return Response::error(e.for_field("request_id")),
+    };
     let request_id = match Uuid::parse_str(&request_id) {
         Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "uuid is invalid"}).to_string()).c_ptr(),
+        Err(_) => return Response::error(Error::invalid_argument("request_id", "uuid is invalid")),
     }.as_bytes().to_vec();
 
-    let sign_data = convert_ptr_string_to_string(sign_data).unwrap();
-    let sign_data_bytes = match hex::decode(remove_prefix_0x(&sign_data)) {
+    let origin = match convert_ptr_string_to_string(origin) {
         Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "sign data is invalid"}).to_string()).c_ptr(),
+        Err(e) => return Response::error(e.for_field("origin")),
     };
-
-    let origin = convert_ptr_string_to_string(origin).unwrap();
     let origin = if origin.len() == 0 { None } else { Some(origin.to_string()) };
 
     let request = CardanoSignRequest::new(
         Some(request_id),
-        sign_data_bytes,
+        sign_data,
         utxos,
         cert_keys,
         origin
     );
 
-    Response::success_object(Box::into_raw(Box::new(request)) as PtrVoid).c_ptr()
+    Response::success_object(request)
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_sign_request_get_ur_encoder(cardano_sign_request: PtrVoid) -> PtrResponse {
+    cardano_sign_request_get_ur_encoder_with_options(cardano_sign_request, 200, 0, 0)
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_sign_request_get_ur_encoder(cardano_sign_request: &mut CardanoSignRequest) -> PtrResponse {
-    let message = cardano_sign_request.to_bytes().unwrap();
-    let ur_encoder = ur::Encoder::new(
-        message.as_slice(),
-        200,
-        CardanoSignRequest::get_registry_type().get_type(),
-    )
-    .unwrap();
-    Response::success_object(Box::into_raw(Box::new(ur_encoder)) as PtrVoid).c_ptr()
+pub extern "C" fn cardano_sign_request_get_ur_encoder_with_options(
+    cardano_sign_request: PtrVoid,
+    max_fragment_len: u32,
+    min_fragment_len: u32,
+    first_seq_num: u32,
+) -> PtrResponse {
+    catch_panic(|| {
+        with_object(cardano_sign_request, |cardano_sign_request: &mut CardanoSignRequest| {
+            let message = match cardano_sign_request.to_bytes() {
+                Ok(v) => v,
+                Err(e) => return Response::error(Error::new(ErrorKind::CborError, e)),
+            };
+            match new_encoder(
+                message.as_slice(),
+                CardanoSignRequest::get_registry_type().get_type(),
+                EncoderOptions::new(max_fragment_len, min_fragment_len, first_seq_num),
+            ) {
+                Ok(ur_encoder) => Response::success_object(ur_encoder),
+                Err(e) => Response::error(e),
+            }
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_sign_request_get_request_id(cardano_sign_request: &mut CardanoSignRequest) -> PtrResponse {
-    cardano_sign_request.get_request_id().map_or(Response::success_null().c_ptr(), |id| {
-        Response::success_string(hex::encode(id)).c_ptr()
+pub extern "C" fn cardano_sign_request_get_request_id(cardano_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(cardano_sign_request, |cardano_sign_request: &mut CardanoSignRequest| {
+            cardano_sign_request.get_request_id().map_or(Response::success_null(), |id| {
+                Response::success_string(hex::encode(id))
+            })
+        })
+        .c_ptr()
     })
 }
 
@@ -250,9 +335,9 @@ mod tests {
     let response = unsafe { Response::from_ptr(response) };
 
     let result = response.value.get_object();
-    let result = unsafe { Box::from_raw(result as *mut CardanoSignRequest) };
+    let result = crate::handle::take::<CardanoSignRequest>(result).ok().unwrap();
 
-    let cbor: Vec<u8> = (*result).try_into().unwrap();
+    let cbor: Vec<u8> = result.try_into().unwrap();
     let cbor = hex::encode(cbor);
 
     assert_eq!(cbor, "a501d825509b1deb4d3b7d4bad9bdd2b0d7b3dcb6d0258a184a400828258204e3a6e7fdcb0d0efa17bf79c13aed2b4cb9baf37fb1aa2e39553d5bd720c5c99038258204e3a6e7fdcb0d0efa17bf79c13aed2b4cb9baf37fb1aa2e39553d5bd720c5c99040182a200581d6179df4c75f7616d7d1fd39cbc1a6ea6b40a0d7b89fea62fc0909b6c370119c350a200581d61c9b0c9761fd1dc0404abd55efc895026628b5035ac623c614fbad0310119c35002198ecb0300a0f5f60382d90899a50158204e3a6e7fdcb0d0efa17bf79c13aed2b4cb9baf37fb1aa2e39553d5bd720c5c9902030368313030303030303004d90130a2018a19073cf5190717f500f500f400f4021a73c5da0a0578676164647231717938616337717179307674756c796c37776e746d737863367765783830677663796a79333371666672686d37736839323779737835736674757730646c66743035647a3363377265767066376a7830786e6c636a7a336736396d71346166646876d90899a50158204e3a6e7fdcb0d0efa17bf79c13aed2b4cb9baf37fb1aa2e39553d5bd720c5c9902040368313830323030303004d90130a2018a19073cf5190717f500f500f401f4021a73c5da0a057867616464723171797a383536393367346672386335356d667978686165386a3275303470796478726771723733766d77707833617a763464676b797267796c6a35796c326d306a6c70647065737779797a6a7330766877766e6c367867396637737372786b7a39300481d9089ca201581ce557890352095f1cf6fd2b7d1a28e3c3cb029f48cf34ff890a28d17602d90130a2018a19073cf5190717f500f502f400f4021a73c5da0a056e63617264616e6f2d77616c6c6574".to_string());
@@ -1,11 +1,12 @@
 use serde::Deserialize;
-use serde_json::json;
 use ur_registry::cardano::cardano_sign_tx_hash_request::CardanoSignTxHashRequest;
 use ur_registry::crypto_key_path::CryptoKeyPath;
 use uuid::Uuid;
 
 use crate::{
-    response::{PtrResponse, Response},
+    error::Error,
+    handle::{free, RegistryObject},
+    response::{catch_panic, PtrResponse, Response},
     types::{PtrString, PtrVoid},
     utils::convert_ptr_string_to_string,
 };
@@ -16,12 +17,25 @@ use crate::{
 // origin: Option<String>,
 // address_list: Vec<String>
 
+impl RegistryObject for CardanoSignTxHashRequest {
+    fn object_type() -> &'static str {
+        "cardano-sign-tx-hash-request"
+    }
+}
+
 #[derive(Deserialize)]
 struct Path {
     xfp: String,
     key_path: String,
 }
 
+#[no_mangle]
+pub extern "C" fn cardano_sign_tx_hash_request_free(request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CardanoSignTxHashRequest>(request).c_ptr()
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn cardano_sign_tx_hash_request_construct(
     request_id: PtrString,
@@ -30,57 +44,76 @@ pub extern "C" fn cardano_sign_tx_hash_request_construct(
     origin: PtrString,
     address_list: PtrString,
 ) -> PtrResponse {
-    let request_id = convert_ptr_string_to_string(request_id).unwrap();
-    let request_id = match Uuid::parse_str(&request_id) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "uuid is invalid"}).to_string()).c_ptr(),
-    }
-    .as_bytes()
-    .to_vec();
-    let tx_hash = convert_ptr_string_to_string(tx_hash).unwrap();
-    let paths = convert_ptr_string_to_string(paths).unwrap();
+    catch_panic(|| {
+        let request_id = match convert_ptr_string_to_string(request_id) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("request_id")).c_ptr(),
+        };
+        let request_id = match Uuid::parse_str(&request_id) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_argument("request_id", "uuid is invalid")).c_ptr(),
+        }
+        .as_bytes()
+        .to_vec();
+        let tx_hash = match convert_ptr_string_to_string(tx_hash) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("tx_hash")).c_ptr(),
+        };
+        let paths = match convert_ptr_string_to_string(paths) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("paths")).c_ptr(),
+        };
 
-    let mut err_msg = "";
+        let mut err_msg = "";
 
-    let paths = match serde_json::from_str::<Vec<Path>>(&paths) {
-        Ok(v) => v,
-        Err(_) => return Response::error(json!({"error": "paths is invalid"}).to_string()).c_ptr(),
-    }
-    .iter()
-    .map(|path| {
-        let xfp = hex::decode(path.xfp.clone()).unwrap();
-        let path = CryptoKeyPath::from_path(
-            path.key_path.clone(),
-            Some(xfp.as_slice().try_into().unwrap()),
-        );
-        match path {
-            Ok(v) => Some(v),
-            Err(_) => {
-                err_msg = "path is invalid";
-                None
-            }
+        let paths = match serde_json::from_str::<Vec<Path>>(&paths) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_argument("paths", "paths is invalid")).c_ptr(),
         }
-    }).filter_map(|v| v)
-    .collect();
-
-    if err_msg.len() > 0 {
-        return Response::error(json!({"error": err_msg}).to_string()).c_ptr()
-    }
+        .iter()
+        .map(|path| {
+            let xfp: [u8; 4] = match hex::decode(path.xfp.clone()).ok().and_then(|v| v.try_into().ok()) {
+                Some(v) => v,
+                None => {
+                    err_msg = "xfp in paths is invalid";
+                    return None
+                }
+            };
+            let path = CryptoKeyPath::from_path(path.key_path.clone(), Some(xfp));
+            match path {
+                Ok(v) => Some(v),
+                Err(_) => {
+                    err_msg = "path is invalid";
+                    None
+                }
+            }
+        }).filter_map(|v| v)
+        .collect();
 
-    let origin = convert_ptr_string_to_string(origin).unwrap();
-    let origin = match origin.is_empty() {
-        true => None,
-        false => Some(origin),
-    };
-    let address_list = convert_ptr_string_to_string(address_list).unwrap();
-    let address_list = match serde_json::from_str::<Vec<String>>(&address_list) {
-        Ok(v) => v,
-        Err(_) => {
-            return Response::error(json!({"error": "address list is invalid"}).to_string()).c_ptr()
+        if err_msg.len() > 0 {
+            return Response::error(Error::invalid_argument("paths", err_msg)).c_ptr()
         }
-    };
-    let cardano_sign_tx_hash_request =
-        CardanoSignTxHashRequest::new(Some(request_id), tx_hash, paths, origin, address_list);
-    Response::success_object(Box::into_raw(Box::new(cardano_sign_tx_hash_request)) as PtrVoid)
-        .c_ptr()
+
+        let origin = match convert_ptr_string_to_string(origin) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("origin")).c_ptr(),
+        };
+        let origin = match origin.is_empty() {
+            true => None,
+            false => Some(origin),
+        };
+        let address_list = match convert_ptr_string_to_string(address_list) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("address_list")).c_ptr(),
+        };
+        let address_list = match serde_json::from_str::<Vec<String>>(&address_list) {
+            Ok(v) => v,
+            Err(_) => {
+                return Response::error(Error::invalid_argument("address_list", "address list is invalid")).c_ptr()
+            }
+        };
+        let cardano_sign_tx_hash_request =
+            CardanoSignTxHashRequest::new(Some(request_id), tx_hash, paths, origin, address_list);
+        Response::success_object(cardano_sign_tx_hash_request).c_ptr()
+    })
 }
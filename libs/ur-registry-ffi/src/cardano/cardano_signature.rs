@@ -1,24 +1,59 @@
+use crate::error::{Error, ErrorKind};
 use hex;
 use ur_registry::cardano::cardano_signature::CardanoSignature;
-use crate::response::{PtrResponse, Response};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
 use crate::types::PtrVoid;
 
+impl RegistryObject for CardanoSignature {
+    fn object_type() -> &'static str {
+        "cardano-signature"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
   match CardanoSignature::try_from(data) {
-      Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-      Err(error) => Response::error(error.to_string()).c_ptr(),
+      Ok(result) => Response::success_object(result).c_ptr(),
+      Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
   }
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_signature_get_witness_set(eth_signature: &mut CardanoSignature) -> PtrResponse {
-  Response::success_string(hex::encode(eth_signature.get_witness_set())).c_ptr()
+pub extern "C" fn cardano_signature_free(cardano_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CardanoSignature>(cardano_signature).c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn cardano_signature_get_request_id(eth_signature: &mut CardanoSignature) -> PtrResponse {
-  match eth_signature.get_request_id() {
-    Some(v) => Response::success_string(hex::encode(v)).c_ptr(),
-    None => Response::error(format!("No request id supplied")).c_ptr()
-  }
+pub extern "C" fn cardano_signature_get_witness_set(cardano_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(cardano_signature, |cardano_signature: &mut CardanoSignature| {
+            Response::success_string(hex::encode(cardano_signature.get_witness_set()))
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_signature_get_witness_set_bytes(cardano_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(cardano_signature, |cardano_signature: &mut CardanoSignature| {
+            Response::success_bytes(cardano_signature.get_witness_set())
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn cardano_signature_get_request_id(cardano_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(cardano_signature, |cardano_signature: &mut CardanoSignature| {
+            match cardano_signature.get_request_id() {
+                Some(v) => Response::success_string(hex::encode(v)),
+                None => Response::error(Error::new(ErrorKind::NotFound, "No request id supplied"))
+            }
+        })
+        .c_ptr()
+    })
 }
@@ -1,32 +1,58 @@
+use crate::error::{Error, ErrorKind};
 use ur_registry::crypto_account::CryptoAccount;
-use crate::response::{PtrResponse, Response};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
 use crate::types::PtrVoid;
 
+impl RegistryObject for CryptoAccount {
+    fn object_type() -> &'static str {
+        "crypto-account"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match ur_registry::crypto_account::CryptoAccount::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
 }
 
 #[no_mangle]
-pub extern "C" fn crypto_account_get_accounts_len(crypto_account: &mut CryptoAccount) -> PtrResponse {
-    Response::success_uint32(crypto_account.get_output_descriptors().len() as u32).c_ptr()
+pub extern "C" fn crypto_account_free(crypto_account: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CryptoAccount>(crypto_account).c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn crypto_account_get_account(crypto_account: &mut CryptoAccount, index: u32) -> PtrResponse {
-    match crypto_account.get_output_descriptors().get(index as usize) {
-        Some(crypto_output) => {
-            Response::success_object(Box::into_raw(Box::new(crypto_output.clone())) as PtrVoid)
-        }
-        None => Response::error(format!("No account for index {} was found", index))
-    }.c_ptr()
+pub extern "C" fn crypto_account_get_accounts_len(crypto_account: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(crypto_account, |crypto_account: &mut CryptoAccount| {
+            Response::success_uint32(crypto_account.get_output_descriptors().len() as u32)
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn crypto_account_get_master_fingerprint(
-    crypto_account: &mut CryptoAccount,
-) -> PtrResponse {
-    Response::success_string(hex::encode(crypto_account.get_master_fingerprint())).c_ptr()
-}
\ No newline at end of file
+pub extern "C" fn crypto_account_get_account(crypto_account: PtrVoid, index: u32) -> PtrResponse {
+    catch_panic(|| {
+        with_object(crypto_account, |crypto_account: &mut CryptoAccount| {
+            match crypto_account.get_output_descriptors().get(index as usize) {
+                Some(crypto_output) => Response::success_object(crypto_output.clone()),
+                None => Response::error(Error::invalid_argument("index", format!("No account for index {} was found", index)))
+            }
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn crypto_account_get_master_fingerprint(crypto_account: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(crypto_account, |crypto_account: &mut CryptoAccount| {
+            Response::success_string(hex::encode(crypto_account.get_master_fingerprint()))
+        })
+        .c_ptr()
+    })
+}
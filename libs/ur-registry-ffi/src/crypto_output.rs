@@ -1,15 +1,38 @@
+use crate::error::{Error, ErrorKind};
 use ur_registry::crypto_output::CryptoOutput;
-use crate::response::{PtrResponse, Response};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
 use crate::types::PtrVoid;
 
+impl RegistryObject for CryptoOutput {
+    fn object_type() -> &'static str {
+        "crypto-output"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match ur_registry::crypto_output::CryptoOutput::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
 }
 
 #[no_mangle]
-pub extern "C" fn crypto_output_get_hd_key(crypto_output: &mut CryptoOutput) -> PtrResponse {
-    Response::success_object(Box::into_raw(Box::new(crypto_output.get_hd_key().unwrap())) as PtrVoid).c_ptr()
-}
\ No newline at end of file
+pub extern "C" fn crypto_output_free(crypto_output: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CryptoOutput>(crypto_output).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn crypto_output_get_hd_key(crypto_output: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(crypto_output, |crypto_output: &mut CryptoOutput| {
+            match crypto_output.get_hd_key() {
+                Some(hd_key) => Response::success_object(hd_key),
+                None => Response::success_null(),
+            }
+        })
+        .c_ptr()
+    })
+}
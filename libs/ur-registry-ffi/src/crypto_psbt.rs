@@ -1,41 +1,105 @@
 use ur_registry::{crypto_psbt::CryptoPSBT, traits::{To, RegistryItem}};
 
 use crate::{
-    response::{PtrResponse, Response},
-    types::{PtrString, PtrVoid},
-    utils::parse_ptr_string_to_bytes,
+    error::{Error, ErrorKind},
+    handle::{free, with_object, RegistryObject},
+    response::{catch_panic, PtrResponse, Response},
+    types::{PtrBytes, PtrString, PtrVoid},
+    ur_encoder::{new_encoder, EncoderOptions, DEFAULT_MAX_FRAGMENT_LEN},
+    utils::{parse_ptr_bytes, parse_ptr_string_to_bytes},
 };
 
+impl RegistryObject for CryptoPSBT {
+    fn object_type() -> &'static str {
+        "crypto-psbt"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match ur_registry::crypto_psbt::CryptoPSBT::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
 }
 
 #[no_mangle]
-pub extern "C" fn crypto_psbt_get_data(crypto_psbt: &mut CryptoPSBT) -> PtrResponse {
-    Response::success_string(hex::encode(crypto_psbt.get_psbt())).c_ptr()
+pub extern "C" fn crypto_psbt_free(crypto_psbt: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CryptoPSBT>(crypto_psbt).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn crypto_psbt_get_data(crypto_psbt: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(crypto_psbt, |crypto_psbt: &mut CryptoPSBT| {
+            Response::success_string(hex::encode(crypto_psbt.get_psbt()))
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn crypto_psbt_get_data_bytes(crypto_psbt: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(crypto_psbt, |crypto_psbt: &mut CryptoPSBT| {
+            Response::success_bytes(crypto_psbt.get_psbt())
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn crypto_psbt_construct(data: PtrString) -> PtrResponse {
-    let psbt = match parse_ptr_string_to_bytes(data).map_err(|e| Response::error(e)) {
-        Ok(v) => v,
-        Err(e) => return e.c_ptr(),
-    };
-    let crypto_psbt = CryptoPSBT::new(psbt);
-    Response::success_object(Box::into_raw(Box::new(crypto_psbt)) as PtrVoid).c_ptr()
+    catch_panic(|| {
+        let psbt = match parse_ptr_string_to_bytes(data).map_err(|e| Response::error(e.for_field("data"))) {
+            Ok(v) => v,
+            Err(e) => return e.c_ptr(),
+        };
+        let crypto_psbt = CryptoPSBT::new(psbt);
+        Response::success_object(crypto_psbt).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn crypto_psbt_construct_from_bytes(data: PtrBytes, len: usize) -> PtrResponse {
+    catch_panic(|| {
+        let psbt = match parse_ptr_bytes(data, len).map_err(|e| Response::error(e.for_field("data"))) {
+            Ok(v) => v,
+            Err(e) => return e.c_ptr(),
+        };
+        let crypto_psbt = CryptoPSBT::new(psbt);
+        Response::success_object(crypto_psbt).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn crypto_psbt_get_ur_encoder(crypto_psbt: PtrVoid) -> PtrResponse {
+    crypto_psbt_get_ur_encoder_with_options(crypto_psbt, DEFAULT_MAX_FRAGMENT_LEN, 0, 0)
 }
 
 #[no_mangle]
-pub extern "C" fn crypto_psbt_get_ur_encoder(crypto_psbt: &mut CryptoPSBT) -> PtrResponse {
-    let message = crypto_psbt.to_bytes().unwrap();
-    let ur_encoder = ur::Encoder::new(
-        message.as_slice(),
-        400,
-        CryptoPSBT::get_registry_type().get_type(),
-    )
-    .unwrap();
-    Response::success_object(Box::into_raw(Box::new(ur_encoder)) as PtrVoid).c_ptr()
+pub extern "C" fn crypto_psbt_get_ur_encoder_with_options(
+    crypto_psbt: PtrVoid,
+    max_fragment_len: u32,
+    min_fragment_len: u32,
+    first_seq_num: u32,
+) -> PtrResponse {
+    catch_panic(|| {
+        with_object(crypto_psbt, |crypto_psbt: &mut CryptoPSBT| {
+            let message = match crypto_psbt.to_bytes() {
+                Ok(v) => v,
+                Err(e) => return Response::error(Error::new(ErrorKind::CborError, e)),
+            };
+            match new_encoder(
+                message.as_slice(),
+                CryptoPSBT::get_registry_type().get_type(),
+                EncoderOptions::new(max_fragment_len, min_fragment_len, first_seq_num),
+            ) {
+                Ok(ur_encoder) => Response::success_object(ur_encoder),
+                Err(e) => Response::error(e),
+            }
+        })
+        .c_ptr()
+    })
 }
@@ -0,0 +1,130 @@
+use serde_json::{json, Map, Value};
+
+/// Kinds of failure reported in `Response.status_code`, `0` is reserved for success.
+///
+/// The matching `error_message` is always a JSON object of the form
+/// `{"kind": "InvalidHex", "field": "sign_data", "error": "Odd number of digits"}`, where `field`
+/// is only present when a single argument is at fault.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    Internal = 1,
+    InvalidArgument = 2,
+    InvalidHex = 3,
+    InvalidPath = 4,
+    UnsupportedType = 5,
+    DecodeFailure = 6,
+    CborError = 7,
+    NotComplete = 8,
+    InvalidHandle = 9,
+    NotFound = 10,
+    TypeMismatch = 11,
+    InvalidScheme = 12,
+    InvalidSequence = 13,
+    InvalidBytewords = 14,
+    InvalidFragment = 15,
+    DifferentMessage = 16,
+    NotSinglePart = 17,
+    LimitExceeded = 18,
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Internal => "Internal",
+            ErrorKind::InvalidArgument => "InvalidArgument",
+            ErrorKind::InvalidHex => "InvalidHex",
+            ErrorKind::InvalidPath => "InvalidPath",
+            ErrorKind::UnsupportedType => "UnsupportedType",
+            ErrorKind::DecodeFailure => "DecodeFailure",
+            ErrorKind::CborError => "CborError",
+            ErrorKind::NotComplete => "NotComplete",
+            ErrorKind::InvalidHandle => "InvalidHandle",
+            ErrorKind::NotFound => "NotFound",
+            ErrorKind::TypeMismatch => "TypeMismatch",
+            ErrorKind::InvalidScheme => "InvalidScheme",
+            ErrorKind::InvalidSequence => "InvalidSequence",
+            ErrorKind::InvalidBytewords => "InvalidBytewords",
+            ErrorKind::InvalidFragment => "InvalidFragment",
+            ErrorKind::DifferentMessage => "DifferentMessage",
+            ErrorKind::NotSinglePart => "NotSinglePart",
+            ErrorKind::LimitExceeded => "LimitExceeded",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+    field: Option<String>,
+    message: String,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, message: impl ToString) -> Self {
+        Error {
+            kind,
+            field: None,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn invalid_argument(field: &str, message: impl ToString) -> Self {
+        Error::new(ErrorKind::InvalidArgument, message).for_field(field)
+    }
+
+    pub fn invalid_hex(field: &str, message: impl ToString) -> Self {
+        Error::new(ErrorKind::InvalidHex, message).for_field(field)
+    }
+
+    pub fn invalid_path(field: &str, message: impl ToString) -> Self {
+        Error::new(ErrorKind::InvalidPath, message).for_field(field)
+    }
+
+    pub fn for_field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_string());
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut error = Map::new();
+        error.insert("kind".to_string(), json!(self.kind.name()));
+        if let Some(field) = &self.field {
+            error.insert("field".to_string(), json!(field));
+        }
+        error.insert("error".to_string(), json!(self.message));
+        Value::Object(error)
+    }
+
+    pub fn to_json(&self) -> String {
+        self.to_value().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let error = Error::invalid_hex("sign_data", "Odd number of digits");
+        assert_eq!(error.kind() as u32, 3);
+        assert_eq!(
+            serde_json::from_str::<Value>(&error.to_json()).unwrap(),
+            json!({"kind": "InvalidHex", "field": "sign_data", "error": "Odd number of digits"})
+        );
+        let error = Error::new(ErrorKind::NotComplete, "not complete");
+        assert_eq!(
+            serde_json::from_str::<Value>(&error.to_json()).unwrap(),
+            json!({"kind": "NotComplete", "error": "not complete"})
+        );
+    }
+}
@@ -0,0 +1,681 @@
+use super::keccak256;
+use crate::error::{Error, ErrorKind};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::PtrString;
+use crate::utils::{convert_ptr_string_to_string, remove_prefix_0x};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// The fields an `EIP712Domain` may declare, any subset of them being allowed.
+const DOMAIN_FIELDS: [(&str, &str); 5] = [
+    ("name", "string"),
+    ("version", "string"),
+    ("chainId", "uint256"),
+    ("verifyingContract", "address"),
+    ("salt", "bytes32"),
+];
+
+/// Bounds on the struct types, whose dependencies are walked recursively.
+const MAX_TYPES: usize = 256;
+const MAX_DEPTH: usize = 32;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Field {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+/// The JSON payload of an `eth_signTypedData_v4` request, as carried by the typed data
+/// `DataType` of `EthSignRequest`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<Field>>,
+    pub primary_type: String,
+    #[serde(default)]
+    pub domain: Value,
+    #[serde(default)]
+    pub message: Value,
+}
+
+fn invalid(message: impl ToString) -> Error {
+    Error::new(ErrorKind::InvalidArgument, message)
+}
+
+fn too_deep(struct_type: &str) -> Error {
+    Error::new(
+        ErrorKind::LimitExceeded,
+        format!("type {} nests structs deeper than {}", struct_type, MAX_DEPTH),
+    )
+}
+
+/// `Person[][2]` -> `Person`
+fn base_type(field_type: &str) -> &str {
+    field_type.split('[').next().unwrap_or(field_type)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Atomic and dynamic types, spelled the canonical way, e.g. `uint256` rather than `uint`.
+fn is_primitive_type(field_type: &str) -> bool {
+    match field_type {
+        "string" | "bytes" | "bool" | "address" => true,
+        _ => match (field_type.strip_prefix("bytes"), field_type.strip_prefix("uint"), field_type.strip_prefix("int")) {
+            (Some(size), _, _) => parse_size(size, 1, 32).is_some(),
+            (_, Some(bits), _) | (_, _, Some(bits)) => parse_size(bits, 8, 256).filter(|v| v % 8 == 0).is_some(),
+            _ => false,
+        },
+    }
+}
+
+impl TypedData {
+    /// Parses and validates typed data, see `validate`.
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let typed_data: Self =
+            serde_json::from_slice(data).map_err(|e| invalid(format!("typed data is invalid: {}", e)))?;
+        typed_data.validate()?;
+        Ok(typed_data)
+    }
+
+    /// Checks that the types are well-formed: struct and field names are identifiers, fields are
+    /// unique and of primitive, struct or array types, the domain only declares the fields of
+    /// EIP-712, the primary type is defined and no struct depends on itself. At most `MAX_TYPES`
+    /// structs nested `MAX_DEPTH` deep are accepted.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.types.len() > MAX_TYPES {
+            return Err(Error::new(
+                ErrorKind::LimitExceeded,
+                format!("more than {} types", MAX_TYPES),
+            ));
+        }
+        for (name, fields) in &self.types {
+            if !is_identifier(name) || is_primitive_type(name) {
+                return Err(invalid(format!("type name {} is invalid", name)));
+            }
+            let mut names = BTreeSet::new();
+            for field in fields {
+                if !is_identifier(&field.name) || !names.insert(field.name.as_str()) {
+                    return Err(invalid(format!("{}.{} is invalid or duplicated", name, field.name)));
+                }
+                self.check_field_type(&field.field_type)
+                    .map_err(|e| invalid(format!("{}.{}: {}", name, field.name, e.message())))?;
+            }
+        }
+        for field in self.fields(DOMAIN_TYPE)? {
+            if !DOMAIN_FIELDS.contains(&(field.name.as_str(), field.field_type.as_str())) {
+                return Err(invalid(format!(
+                    "{} field {} {} is not allowed",
+                    DOMAIN_TYPE, field.field_type, field.name
+                )));
+            }
+        }
+        self.fields(&self.primary_type)?;
+        let mut heights = BTreeMap::new();
+        for name in self.types.keys() {
+            self.check_acyclic(name, &mut vec![], &mut heights)?;
+        }
+        Ok(())
+    }
+
+    fn check_field_type(&self, field_type: &str) -> Result<(), Error> {
+        let mut item_type = field_type;
+        while let Some(inner) = item_type.strip_suffix(']') {
+            let (inner_type, len) = inner
+                .rsplit_once('[')
+                .ok_or_else(|| invalid(format!("type {} is invalid", field_type)))?;
+            if !len.is_empty() && parse_size(len, 1, usize::MAX).is_none() {
+                return Err(invalid(format!("type {} is invalid", field_type)));
+            }
+            item_type = inner_type;
+        }
+        if is_primitive_type(item_type) || self.types.contains_key(item_type) {
+            return Ok(());
+        }
+        Err(invalid(format!("type {} is not defined", item_type)))
+    }
+
+    /// Depth-first search of the struct dependencies, `path` being the structs being visited,
+    /// returning how many structs deep `struct_type` nests. `heights` keeps that of the structs
+    /// already visited.
+    fn check_acyclic<'a>(
+        &'a self,
+        struct_type: &'a str,
+        path: &mut Vec<&'a str>,
+        heights: &mut BTreeMap<&'a str, usize>,
+    ) -> Result<usize, Error> {
+        if let Some(height) = heights.get(struct_type) {
+            return Ok(*height);
+        }
+        if let Some(start) = path.iter().position(|v| *v == struct_type) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(struct_type);
+            return Err(invalid(format!("type {} is recursive: {}", struct_type, cycle.join(" -> "))));
+        }
+        if path.len() >= MAX_DEPTH {
+            return Err(too_deep(path[0]));
+        }
+        path.push(struct_type);
+        let mut height = 1;
+        for field in self.fields(struct_type)? {
+            let base = base_type(&field.field_type);
+            if self.types.contains_key(base) {
+                height = height.max(self.check_acyclic(base, path, heights)? + 1);
+            }
+        }
+        path.pop();
+        if height > MAX_DEPTH {
+            return Err(too_deep(struct_type));
+        }
+        heights.insert(struct_type, height);
+        Ok(height)
+    }
+
+    fn fields(&self, struct_type: &str) -> Result<&Vec<Field>, Error> {
+        self.types
+            .get(struct_type)
+            .ok_or_else(|| invalid(format!("type {} is not defined", struct_type)))
+    }
+
+    /// Stops `MAX_DEPTH` structs deep, as types built without `validate` may nest further.
+    fn collect_dependencies(&self, struct_type: &str, depth: usize, found: &mut BTreeSet<String>) -> Result<(), Error> {
+        if !found.insert(struct_type.to_string()) {
+            return Ok(());
+        }
+        if depth >= MAX_DEPTH {
+            return Err(too_deep(struct_type));
+        }
+        for field in self.fields(struct_type)? {
+            let base = base_type(&field.field_type);
+            if self.types.contains_key(base) {
+                self.collect_dependencies(base, depth + 1, found)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `Mail(Person from,Person to,string contents)Person(string name,address wallet)`
+    pub fn encode_type(&self, struct_type: &str) -> Result<String, Error> {
+        let mut dependencies = BTreeSet::new();
+        self.collect_dependencies(struct_type, 0, &mut dependencies)?;
+        dependencies.remove(struct_type);
+        let mut encoded = String::new();
+        for name in std::iter::once(struct_type).chain(dependencies.iter().map(|v| v.as_str())) {
+            let fields: Vec<String> = self
+                .fields(name)?
+                .iter()
+                .map(|f| format!("{} {}", f.field_type, f.name))
+                .collect();
+            encoded.push_str(&format!("{}({})", name, fields.join(",")));
+        }
+        Ok(encoded)
+    }
+
+    pub fn type_hash(&self, struct_type: &str) -> Result<[u8; 32], Error> {
+        Ok(keccak256(self.encode_type(struct_type)?.as_bytes()))
+    }
+
+    pub fn encode_data(&self, struct_type: &str, data: &Value) -> Result<Vec<u8>, Error> {
+        let data = data
+            .as_object()
+            .ok_or_else(|| invalid(format!("value of {} must be an object", struct_type)))?;
+        let mut encoded = self.type_hash(struct_type)?.to_vec();
+        for field in self.fields(struct_type)? {
+            let value = data
+                .get(&field.name)
+                .ok_or_else(|| invalid(format!("{}.{} is missing", struct_type, field.name)))?;
+            let word = self
+                .encode_value(&field.field_type, value)
+                .map_err(|e| invalid(format!("{}.{}: {}", struct_type, field.name, e.message())))?;
+            encoded.extend_from_slice(&word);
+        }
+        Ok(encoded)
+    }
+
+    pub fn hash_struct(&self, struct_type: &str, data: &Value) -> Result<[u8; 32], Error> {
+        Ok(keccak256(&self.encode_data(struct_type, data)?))
+    }
+
+    pub fn domain_separator(&self) -> Result<[u8; 32], Error> {
+        self.hash_struct(DOMAIN_TYPE, &self.domain)
+    }
+
+    /// `hashStruct(message)`, `None` when the primary type is the domain itself.
+    pub fn message_hash(&self) -> Result<Option<[u8; 32]>, Error> {
+        match self.primary_type.as_str() {
+            DOMAIN_TYPE => Ok(None),
+            primary_type => self.hash_struct(primary_type, &self.message).map(Some),
+        }
+    }
+
+    /// `keccak256(0x19 0x01 ‖ domainSeparator ‖ hashStruct(message))`, the message hash being
+    /// left out when the primary type is the domain itself.
+    pub fn digest(&self) -> Result<[u8; 32], Error> {
+        let mut data = vec![0x19, 0x01];
+        data.extend_from_slice(&self.domain_separator()?);
+        if let Some(message_hash) = self.message_hash()? {
+            data.extend_from_slice(&message_hash);
+        }
+        Ok(keccak256(&data))
+    }
+
+    /// Pairs every value with its declared type for display, as `{"name", "type", "value"}` for
+    /// atomic values and `{"name", "type", "children"}` for structs and arrays.
+    pub fn describe(&self, name: &str, field_type: &str, value: &Value) -> Result<Value, Error> {
+        if let Some(inner) = field_type.strip_suffix(']') {
+            let (item_type, _) = inner
+                .rsplit_once('[')
+                .ok_or_else(|| invalid(format!("type {} is invalid", field_type)))?;
+            let items = value
+                .as_array()
+                .ok_or_else(|| invalid(format!("{} value must be an array", field_type)))?;
+            let children = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| self.describe(&i.to_string(), item_type, item))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(json!({"name": name, "type": field_type, "children": children}));
+        }
+        if !self.types.contains_key(field_type) {
+            return Ok(json!({"name": name, "type": field_type, "value": value}));
+        }
+        let data = value
+            .as_object()
+            .ok_or_else(|| invalid(format!("value of {} must be an object", field_type)))?;
+        let children = self
+            .fields(field_type)?
+            .iter()
+            .map(|field| match data.get(&field.name) {
+                Some(value) => self.describe(&field.name, &field.field_type, value),
+                None => Err(invalid(format!("{}.{} is missing", field_type, field.name))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(json!({"name": name, "type": field_type, "children": children}))
+    }
+
+    fn encode_value(&self, field_type: &str, value: &Value) -> Result<[u8; 32], Error> {
+        if let Some(inner) = field_type.strip_suffix(']') {
+            let (item_type, len) = inner
+                .rsplit_once('[')
+                .ok_or_else(|| invalid(format!("type {} is invalid", field_type)))?;
+            let items = value
+                .as_array()
+                .ok_or_else(|| invalid(format!("{} value must be an array", field_type)))?;
+            if !len.is_empty() && len.parse::<usize>().ok() != Some(items.len()) {
+                return Err(invalid(format!("{} value must have {} items", field_type, len)));
+            }
+            let mut encoded = vec![];
+            for item in items {
+                encoded.extend_from_slice(&self.encode_value(item_type, item)?);
+            }
+            return Ok(keccak256(&encoded));
+        }
+        if self.types.contains_key(field_type) {
+            return self.hash_struct(field_type, value);
+        }
+        match field_type {
+            "string" => value
+                .as_str()
+                .map(|v| keccak256(v.as_bytes()))
+                .ok_or_else(|| invalid("string value expected")),
+            "bytes" => Ok(keccak256(&parse_hex_value(value)?)),
+            "bool" => match value {
+                Value::Bool(v) => Ok(word_from_bytes(&[*v as u8])),
+                _ => Err(invalid("bool value expected")),
+            },
+            "address" => {
+                let address = parse_hex_value(value)?;
+                if address.len() != 20 {
+                    return Err(invalid("address must be 20 bytes"));
+                }
+                Ok(word_from_bytes(&address))
+            }
+            _ => {
+                if let Some(size) = field_type.strip_prefix("bytes") {
+                    let size = parse_size(size, 1, 32).ok_or_else(|| invalid(format!("type {} is invalid", field_type)))?;
+                    let bytes = parse_hex_value(value)?;
+                    if bytes.len() != size {
+                        return Err(invalid(format!("{} value must be {} bytes", field_type, size)));
+                    }
+                    let mut word = [0u8; 32];
+                    word[..size].copy_from_slice(&bytes);
+                    return Ok(word);
+                }
+                let (signed, bits) = match field_type.strip_prefix("uint") {
+                    Some(bits) => (false, bits),
+                    None => match field_type.strip_prefix("int") {
+                        Some(bits) => (true, bits),
+                        None => return Err(invalid(format!("type {} is not defined", field_type))),
+                    },
+                };
+                let bits = parse_size(bits, 8, 256)
+                    .filter(|v| v % 8 == 0)
+                    .ok_or_else(|| invalid(format!("type {} is invalid", field_type)))?;
+                encode_integer(value, signed, bits).ok_or_else(|| invalid(format!("{} value is out of range", field_type)))
+            }
+        }
+    }
+}
+
+/// Plain decimal sizes only, `uint` or `uint0256` are not canonical.
+fn parse_size(size: &str, min: usize, max: usize) -> Option<usize> {
+    if size.starts_with('0') || !size.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let size = size.parse().ok()?;
+    (min..=max).contains(&size).then_some(size)
+}
+
+fn parse_hex_value(value: &Value) -> Result<Vec<u8>, Error> {
+    let value = value.as_str().ok_or_else(|| invalid("hex string expected"))?;
+    hex::decode(remove_prefix_0x(value)).map_err(|e| Error::new(ErrorKind::InvalidHex, e))
+}
+
+/// Left pads up to 32 bytes, the encoding of every atomic value but `bytesN`.
+fn word_from_bytes(bytes: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(bytes);
+    word
+}
+
+fn bit_len(word: &[u8; 32]) -> usize {
+    match word.iter().position(|b| *b != 0) {
+        Some(i) => (32 - i) * 8 - word[i].leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+/// Parses a JSON number, a decimal string or a `0x` hex string into its magnitude and sign.
+pub fn parse_integer(value: &Value) -> Option<(bool, [u8; 32])> {
+    match value {
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(v), _) => Some((false, word_from_bytes(&v.to_be_bytes()))),
+            (None, Some(v)) => Some((true, word_from_bytes(&v.unsigned_abs().to_be_bytes()))),
+            _ => None,
+        },
+        Value::String(s) => {
+            let (negative, digits) = match s.trim().strip_prefix('-') {
+                Some(v) => (true, v),
+                None => (false, s.trim()),
+            };
+            let magnitude = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+                Some(hex_digits) => {
+                    let hex_digits = if hex_digits.len() % 2 == 1 {
+                        format!("0{}", hex_digits)
+                    } else {
+                        hex_digits.to_string()
+                    };
+                    let bytes = hex::decode(hex_digits).ok()?;
+                    let bytes = &bytes[bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len())..];
+                    if bytes.len() > 32 {
+                        return None;
+                    }
+                    word_from_bytes(bytes)
+                }
+                None => parse_decimal(digits)?,
+            };
+            Some((negative, magnitude))
+        }
+        _ => None,
+    }
+}
+
+fn parse_decimal(digits: &str) -> Option<[u8; 32]> {
+    if digits.is_empty() {
+        return None;
+    }
+    let mut word = [0u8; 32];
+    for c in digits.chars() {
+        let mut carry = c.to_digit(10)?;
+        for byte in word.iter_mut().rev() {
+            let v = *byte as u32 * 10 + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(word)
+}
+
+/// Two's complement encoding of a `uint<bits>` or `int<bits>` value.
+fn encode_integer(value: &Value, signed: bool, bits: usize) -> Option<[u8; 32]> {
+    let (negative, magnitude) = parse_integer(value)?;
+    let len = bit_len(&magnitude);
+    if !signed {
+        return ((!negative || len == 0) && len <= bits).then_some(magnitude);
+    }
+    if !negative || len == 0 {
+        return (len < bits).then_some(magnitude);
+    }
+    // the magnitude of a negative value may reach 2^(bits-1) itself
+    let is_min = len == bits && magnitude.iter().map(|b| b.count_ones()).sum::<u32>() == 1;
+    if len >= bits && !is_min {
+        return None;
+    }
+    let mut word = magnitude.map(|b| !b);
+    for byte in word.iter_mut().rev() {
+        let (v, overflow) = byte.overflowing_add(1);
+        *byte = v;
+        if !overflow {
+            break;
+        }
+    }
+    Some(word)
+}
+
+/// Validates `typed_data`, the JSON of an `eth_signTypedData_v4` request, and hashes it as
+/// `{"domainSeparator": <hex>, "messageHash": <hex>, "digest": <hex>}`, `messageHash` being
+/// `null` when the primary type is `EIP712Domain`.
+#[no_mangle]
+pub extern "C" fn eth_typed_data_hash(typed_data: PtrString) -> PtrResponse {
+    catch_panic(|| {
+        let typed_data = match convert_ptr_string_to_string(typed_data) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("typed_data")).c_ptr(),
+        };
+        let hashes = TypedData::parse(typed_data.as_bytes()).and_then(|typed_data| {
+            Ok(json!({
+                "domainSeparator": hex::encode(typed_data.domain_separator()?),
+                "messageHash": typed_data.message_hash()?.map(hex::encode),
+                "digest": hex::encode(typed_data.digest()?),
+            }))
+        });
+        match hashes {
+            Ok(hashes) => Response::success_string(hashes.to_string()),
+            Err(e) => Response::error(e.for_field("typed_data")),
+        }
+        .c_ptr()
+    })
+}
+
+/// The Mail example of EIP-712, shared by the tests that hash typed data.
+#[cfg(test)]
+pub const MAIL_TYPED_DATA: &str = r#"{
+    "types": {
+        "EIP712Domain": [
+            {"name": "name", "type": "string"},
+            {"name": "version", "type": "string"},
+            {"name": "chainId", "type": "uint256"},
+            {"name": "verifyingContract", "type": "address"}
+        ],
+        "Person": [{"name": "name", "type": "string"}, {"name": "wallet", "type": "address"}],
+        "Mail": [
+            {"name": "from", "type": "Person"},
+            {"name": "to", "type": "Person"},
+            {"name": "contents", "type": "string"}
+        ]
+    },
+    "primaryType": "Mail",
+    "domain": {
+        "name": "Ether Mail",
+        "version": "1",
+        "chainId": 1,
+        "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+    },
+    "message": {
+        "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+        "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+        "contents": "Hello, Bob!"
+    }
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethereum::eth_signer::{address, Signature};
+
+    fn with_types(types: Value) -> Result<TypedData, Error> {
+        let mut typed_data: Value = serde_json::from_str(MAIL_TYPED_DATA).unwrap();
+        typed_data["types"] = types;
+        TypedData::parse(typed_data.to_string().as_bytes())
+    }
+
+    #[test]
+    fn test_eip712_example() {
+        let typed_data = TypedData::parse(MAIL_TYPED_DATA.as_bytes()).unwrap();
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            hex::encode(typed_data.type_hash("Mail").unwrap()),
+            "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+        );
+        assert_eq!(
+            hex::encode(typed_data.domain_separator().unwrap()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(typed_data.message_hash().unwrap().unwrap()),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        let digest = typed_data.digest().unwrap();
+        assert_eq!(
+            hex::encode(digest),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+
+        // signed by keccak256("cow")
+        let signature = hex::decode(
+            "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d\
+             07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c",
+        )
+        .unwrap();
+        let public_key = Signature::parse(&signature).unwrap().recover(&digest).unwrap();
+        assert_eq!(hex::encode(address(&public_key)), "cd2a3d9f938e13cd947ec05abc7fe734df8dd826");
+    }
+
+    #[test]
+    fn test_domain_only() {
+        let mut typed_data = TypedData::parse(MAIL_TYPED_DATA.as_bytes()).unwrap();
+        typed_data.primary_type = DOMAIN_TYPE.to_string();
+        assert_eq!(typed_data.message_hash().unwrap(), None);
+        let mut data = vec![0x19, 0x01];
+        data.extend_from_slice(&typed_data.domain_separator().unwrap());
+        assert_eq!(typed_data.digest().unwrap(), keccak256(&data));
+    }
+
+    #[test]
+    fn test_rejects_malformed_types() {
+        let domain = json!([{"name": "name", "type": "string"}]);
+        let person = json!([{"name": "name", "type": "string"}, {"name": "wallet", "type": "address"}]);
+        let mail = |to: &str| json!([{"name": "from", "type": "Person"}, {"name": "to", "type": to}]);
+        let error = |types: Value| with_types(types).err().map(|e| e.kind());
+
+        assert!(with_types(json!({"EIP712Domain": domain, "Person": person, "Mail": mail("Person[][3]")})).is_ok());
+        for to in ["Person[0]", "Person[", "Person[03]", "Animal", "uint", "bytes33", "int7", "uint264"] {
+            assert_eq!(
+                error(json!({"EIP712Domain": domain, "Person": person, "Mail": mail(to)})),
+                Some(ErrorKind::InvalidArgument),
+                "{}",
+                to
+            );
+        }
+        assert_eq!(error(json!({"Person": person, "Mail": mail("Person")})), Some(ErrorKind::InvalidArgument));
+        assert_eq!(
+            error(json!({"EIP712Domain": [{"name": "owner", "type": "address"}], "Person": person, "Mail": mail("Person")})),
+            Some(ErrorKind::InvalidArgument)
+        );
+        assert_eq!(
+            error(json!({"EIP712Domain": domain, "Person": person})),
+            Some(ErrorKind::InvalidArgument)
+        );
+        let duplicated = json!([{"name": "name", "type": "string"}, {"name": "name", "type": "address"}]);
+        assert_eq!(
+            error(json!({"EIP712Domain": domain, "Person": duplicated, "Mail": mail("Person")})),
+            Some(ErrorKind::InvalidArgument)
+        );
+        assert_eq!(
+            error(json!({"EIP712Domain": domain, "Person": person, "Mail": mail("Person"), "uint8": person})),
+            Some(ErrorKind::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_rejects_recursive_types() {
+        let domain = json!([{"name": "name", "type": "string"}]);
+        let mail = json!([{"name": "from", "type": "Person"}]);
+        let recursive = json!({
+            "EIP712Domain": domain,
+            "Mail": mail,
+            "Person": [{"name": "friends", "type": "Person[]"}],
+        });
+        let error = with_types(recursive).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidArgument);
+        assert!(error.message().contains("Person -> Person"), "{}", error.message());
+
+        let indirect = json!({
+            "EIP712Domain": domain,
+            "Mail": mail,
+            "Person": [{"name": "pet", "type": "Animal"}],
+            "Animal": [{"name": "owners", "type": "Person[2]"}],
+        });
+        let error = with_types(indirect).err().unwrap();
+        assert!(error.message().contains("Animal -> Person -> Animal"), "{}", error.message());
+    }
+
+    #[test]
+    fn test_type_limits() {
+        // `head` holds a `T0`, which holds a `T1` and so on
+        let chain_from = |head: &str, len: usize| {
+            let mut types = serde_json::Map::new();
+            types.insert(DOMAIN_TYPE.to_string(), json!([{"name": "name", "type": "string"}]));
+            types.insert("Mail".to_string(), json!([{"name": "contents", "type": "string"}]));
+            types.insert(head.to_string(), json!([{"name": "next", "type": "T0"}]));
+            for i in 0..len {
+                let next = if i + 1 == len { "string".to_string() } else { format!("T{}[]", i + 1) };
+                types.insert(format!("T{}", i), json!([{"name": "next", "type": next}]));
+            }
+            Value::Object(types)
+        };
+        let chain = |len: usize| chain_from("Mail", len);
+        assert!(with_types(chain(MAX_DEPTH - 1)).is_ok());
+        let error = with_types(chain(MAX_DEPTH)).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+        // the chain is visited before its head, which sorts last
+        assert!(with_types(chain_from("Z", MAX_DEPTH - 1)).is_ok());
+        let error = with_types(chain_from("Z", MAX_DEPTH)).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+
+        let mut types = chain(1);
+        for i in 0..MAX_TYPES {
+            types[format!("Unused{}", i)] = json!([{"name": "name", "type": "string"}]);
+        }
+        assert_eq!(with_types(types).err().map(|e| e.kind()), Some(ErrorKind::LimitExceeded));
+
+        // types that were not validated are still walked within the depth
+        let mut typed_data = TypedData::parse(MAIL_TYPED_DATA.as_bytes()).unwrap();
+        typed_data.types = serde_json::from_value(chain(MAX_DEPTH + 1)).unwrap();
+        assert_eq!(typed_data.encode_type("Mail").err().map(|e| e.kind()), Some(ErrorKind::LimitExceeded));
+        assert!(typed_data.encode_type("T2").is_ok());
+    }
+}
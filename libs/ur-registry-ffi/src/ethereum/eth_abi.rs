@@ -0,0 +1,397 @@
+use crate::error::{Error, ErrorKind};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::PtrString;
+use crate::utils::{convert_ptr_string_to_string, parse_ptr_string_to_bytes};
+use ethabi::ethereum_types::U256;
+use ethabi::{Contract, Function, Token};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+const ERC20_ABI: &str = r#"[
+    {"type": "function", "name": "transfer", "stateMutability": "nonpayable", "outputs": [{"name": "", "type": "bool"}],
+     "inputs": [{"name": "to", "type": "address"}, {"name": "amount", "type": "uint256"}]},
+    {"type": "function", "name": "approve", "stateMutability": "nonpayable", "outputs": [{"name": "", "type": "bool"}],
+     "inputs": [{"name": "spender", "type": "address"}, {"name": "amount", "type": "uint256"}]},
+    {"type": "function", "name": "transferFrom", "stateMutability": "nonpayable", "outputs": [{"name": "", "type": "bool"}],
+     "inputs": [{"name": "from", "type": "address"}, {"name": "to", "type": "address"}, {"name": "amount", "type": "uint256"}]},
+    {"type": "function", "name": "increaseAllowance", "stateMutability": "nonpayable", "outputs": [{"name": "", "type": "bool"}],
+     "inputs": [{"name": "spender", "type": "address"}, {"name": "addedValue", "type": "uint256"}]}
+]"#;
+
+const ERC721_ABI: &str = r#"[
+    {"type": "function", "name": "approve", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "to", "type": "address"}, {"name": "tokenId", "type": "uint256"}]},
+    {"type": "function", "name": "transferFrom", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "from", "type": "address"}, {"name": "to", "type": "address"}, {"name": "tokenId", "type": "uint256"}]},
+    {"type": "function", "name": "safeTransferFrom", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "from", "type": "address"}, {"name": "to", "type": "address"}, {"name": "tokenId", "type": "uint256"}]},
+    {"type": "function", "name": "safeTransferFrom", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "from", "type": "address"}, {"name": "to", "type": "address"}, {"name": "tokenId", "type": "uint256"},
+                {"name": "data", "type": "bytes"}]},
+    {"type": "function", "name": "setApprovalForAll", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "operator", "type": "address"}, {"name": "approved", "type": "bool"}]}
+]"#;
+
+const ERC1155_ABI: &str = r#"[
+    {"type": "function", "name": "safeTransferFrom", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "from", "type": "address"}, {"name": "to", "type": "address"}, {"name": "id", "type": "uint256"},
+                {"name": "amount", "type": "uint256"}, {"name": "data", "type": "bytes"}]},
+    {"type": "function", "name": "safeBatchTransferFrom", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "from", "type": "address"}, {"name": "to", "type": "address"}, {"name": "ids", "type": "uint256[]"},
+                {"name": "amounts", "type": "uint256[]"}, {"name": "data", "type": "bytes"}]},
+    {"type": "function", "name": "setApprovalForAll", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "operator", "type": "address"}, {"name": "approved", "type": "bool"}]}
+]"#;
+
+/// EIP-2612 and the DAI flavour of `permit` that predates it.
+const PERMIT_ABI: &str = r#"[
+    {"type": "function", "name": "permit", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "owner", "type": "address"}, {"name": "spender", "type": "address"}, {"name": "value", "type": "uint256"},
+                {"name": "deadline", "type": "uint256"}, {"name": "v", "type": "uint8"}, {"name": "r", "type": "bytes32"},
+                {"name": "s", "type": "bytes32"}]},
+    {"type": "function", "name": "permit", "stateMutability": "nonpayable", "outputs": [],
+     "inputs": [{"name": "holder", "type": "address"}, {"name": "spender", "type": "address"}, {"name": "nonce", "type": "uint256"},
+                {"name": "expiry", "type": "uint256"}, {"name": "allowed", "type": "bool"}, {"name": "v", "type": "uint8"},
+                {"name": "r", "type": "bytes32"}, {"name": "s", "type": "bytes32"}]}
+]"#;
+
+const APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+const INCREASE_ALLOWANCE: [u8; 4] = [0x39, 0x50, 0x93, 0x51];
+const SET_APPROVAL_FOR_ALL: [u8; 4] = [0xa2, 0x2c, 0xb4, 0x65];
+const EIP2612_PERMIT: [u8; 4] = [0xd5, 0x05, 0xac, 0xcf];
+const DAI_PERMIT: [u8; 4] = [0x8f, 0xcb, 0xaf, 0x0c];
+
+struct KnownFunction {
+    function: Function,
+    standards: Vec<&'static str>,
+}
+
+/// Functions by selector, seeded with the token standards and extended by `eth_abi_register`.
+/// Where standards share a selector, as ERC-20 and ERC-721 do for `approve`, the first
+/// definition is kept and every standard is listed.
+fn registry() -> MutexGuard<'static, HashMap<[u8; 4], KnownFunction>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<[u8; 4], KnownFunction>>> = OnceLock::new();
+    REGISTRY
+        .get_or_init(|| {
+            let mut functions = HashMap::new();
+            for (standard, abi) in [
+                ("erc20", ERC20_ABI),
+                ("erc721", ERC721_ABI),
+                ("erc1155", ERC1155_ABI),
+                ("permit", PERMIT_ABI),
+            ] {
+                let contract = Contract::load(abi.as_bytes()).expect("built-in ABI is valid");
+                for function in contract.functions() {
+                    functions
+                        .entry(function.short_signature())
+                        .or_insert_with(|| KnownFunction {
+                            function: function.clone(),
+                            standards: vec![],
+                        })
+                        .standards
+                        .push(standard);
+                }
+            }
+            Mutex::new(functions)
+        })
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Adds every function of a JSON ABI, replacing functions registered before with the same
+/// selector, and returns how many were added. The token standards cannot be overridden: their
+/// functions are skipped when the ABI declares them as well and the whole ABI is rejected when
+/// another function has the selector of one of them.
+pub fn register_abi(abi: &str) -> Result<u32, Error> {
+    let contract = Contract::load(abi.as_bytes()).map_err(|e| Error::invalid_argument("abi", e))?;
+    let mut registry = registry();
+    let mut functions = vec![];
+    for function in contract.functions() {
+        match registry.get(&function.short_signature()) {
+            Some(known) if !known.standards.is_empty() => {
+                if signature(&known.function) != signature(function) {
+                    let message = format!("{} has the selector of {}", signature(function), signature(&known.function));
+                    return Err(Error::invalid_argument("abi", message));
+                }
+            }
+            _ => functions.push(function.clone()),
+        }
+    }
+    let count = functions.len() as u32;
+    for function in functions {
+        registry.insert(
+            function.short_signature(),
+            KnownFunction {
+                function,
+                standards: vec![],
+            },
+        );
+    }
+    Ok(count)
+}
+
+fn signature(function: &Function) -> String {
+    let inputs: Vec<String> = function.inputs.iter().map(|p| p.kind.to_string()).collect();
+    format!("{}({})", function.name, inputs.join(","))
+}
+
+/// Addresses and bytes as hex, integers as decimal strings.
+fn token_value(token: &Token) -> Value {
+    match token {
+        Token::Address(v) => json!(hex::encode(v.as_bytes())),
+        Token::FixedBytes(v) | Token::Bytes(v) => json!(hex::encode(v)),
+        Token::Uint(v) => json!(v.to_string()),
+        Token::Int(v) if v.bit(255) => json!(format!("-{}", (!*v).overflowing_add(U256::one()).0)),
+        Token::Int(v) => json!(v.to_string()),
+        Token::Bool(v) => json!(v),
+        Token::String(v) => json!(v),
+        Token::FixedArray(v) | Token::Array(v) | Token::Tuple(v) => Value::Array(v.iter().map(token_value).collect()),
+    }
+}
+
+/// An allowance of the whole balance: an `approve`, `increaseAllowance` or EIP-2612 `permit` of
+/// the maximum amount, `setApprovalForAll` or an `allowed` DAI `permit`. Matched on the selectors
+/// of the token standards, which registered ABIs cannot take over.
+fn is_unlimited_approval(selector: [u8; 4], tokens: &[Token]) -> bool {
+    let is_max = |i: usize| matches!(tokens.get(i), Some(Token::Uint(v)) if *v == U256::MAX);
+    let is_true = |i: usize| matches!(tokens.get(i), Some(Token::Bool(true)));
+    match selector {
+        APPROVE | INCREASE_ALLOWANCE => is_max(1),
+        SET_APPROVAL_FOR_ALL => is_true(1),
+        EIP2612_PERMIT => is_max(2),
+        DAI_PERMIT => is_true(4),
+        _ => false,
+    }
+}
+
+/// Decodes calldata against the known functions, e.g.
+/// `{"selector": "095ea7b3", "function": "approve", "signature": "approve(address,uint256)",
+/// "standards": ["erc20", "erc721"], "arguments": [{"name": "spender", "type": "address",
+/// "value": <hex>}, ...], "unlimitedApproval": true}`.
+pub fn decode_calldata(data: &[u8]) -> Result<Value, Error> {
+    if data.len() < 4 {
+        return Err(Error::invalid_argument("data", "calldata must start with a 4 byte selector"));
+    }
+    let selector: [u8; 4] = data[..4].try_into().unwrap();
+    let registry = registry();
+    let known = registry.get(&selector).ok_or_else(|| {
+        Error::new(ErrorKind::NotFound, format!("function {} is unknown", hex::encode(selector)))
+    })?;
+    let function = &known.function;
+    let tokens = function
+        .decode_input(&data[4..])
+        .map_err(|e| Error::new(ErrorKind::DecodeFailure, format!("{}: {}", signature(function), e)))?;
+    let arguments: Vec<Value> = function
+        .inputs
+        .iter()
+        .zip(tokens.iter())
+        .map(|(param, token)| json!({"name": param.name, "type": param.kind.to_string(), "value": token_value(token)}))
+        .collect();
+    Ok(json!({
+        "selector": hex::encode(selector),
+        "function": function.name,
+        "signature": signature(function),
+        "standards": known.standards,
+        "arguments": arguments,
+        "unlimitedApproval": is_unlimited_approval(selector, &tokens),
+    }))
+}
+
+/// Registers the functions of a JSON ABI for `eth_abi_decode_calldata` and the transaction
+/// summaries, returning how many functions were registered. ABIs with a function that has the
+/// selector of a token standard function are rejected, see `register_abi`.
+#[no_mangle]
+pub extern "C" fn eth_abi_register(abi: PtrString) -> PtrResponse {
+    catch_panic(|| {
+        let abi = match convert_ptr_string_to_string(abi) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("abi")).c_ptr(),
+        };
+        match register_abi(&abi) {
+            Ok(count) => Response::success_uint32(count),
+            Err(e) => Response::error(e),
+        }
+        .c_ptr()
+    })
+}
+
+/// See `decode_calldata`, `data` is the hex calldata including the selector.
+#[no_mangle]
+pub extern "C" fn eth_abi_decode_calldata(data: PtrString) -> PtrResponse {
+    catch_panic(|| {
+        let data = match parse_ptr_string_to_bytes(data) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("data")).c_ptr(),
+        };
+        match decode_calldata(&data) {
+            Ok(call) => Response::success_string(call.to_string()),
+            Err(e) => Response::error(e),
+        }
+        .c_ptr()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::Address;
+
+    fn calldata(signature: &str, tokens: &[Token]) -> Vec<u8> {
+        let mut data = crate::ethereum::keccak256(signature.as_bytes())[..4].to_vec();
+        data.extend(ethabi::encode(tokens));
+        data
+    }
+
+    #[test]
+    fn test_erc20_calls() {
+        let to = Address::repeat_byte(0x35);
+        let data = calldata("transfer(address,uint256)", &[Token::Address(to), Token::Uint(U256::exp10(18))]);
+        assert_eq!(hex::encode(&data[..4]), "a9059cbb");
+        assert_eq!(
+            decode_calldata(&data).unwrap(),
+            json!({
+                "selector": "a9059cbb",
+                "function": "transfer",
+                "signature": "transfer(address,uint256)",
+                "standards": ["erc20"],
+                "arguments": [
+                    {"name": "to", "type": "address", "value": "35".repeat(20)},
+                    {"name": "amount", "type": "uint256", "value": "1000000000000000000"},
+                ],
+                "unlimitedApproval": false,
+            })
+        );
+
+        let data = calldata("approve(address,uint256)", &[Token::Address(to), Token::Uint(U256::MAX)]);
+        let call = decode_calldata(&data).unwrap();
+        assert_eq!(call["standards"], json!(["erc20", "erc721"]));
+        assert_eq!(call["unlimitedApproval"], true);
+        let data = calldata("approve(address,uint256)", &[Token::Address(to), Token::Uint(U256::from(100))]);
+        assert_eq!(decode_calldata(&data).unwrap()["unlimitedApproval"], false);
+    }
+
+    #[test]
+    fn test_nft_calls() {
+        let (from, to) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let data = calldata("setApprovalForAll(address,bool)", &[Token::Address(to), Token::Bool(true)]);
+        let call = decode_calldata(&data).unwrap();
+        assert_eq!(call["standards"], json!(["erc721", "erc1155"]));
+        assert_eq!(call["unlimitedApproval"], true);
+
+        let ids = Token::Array(vec![Token::Uint(U256::from(1)), Token::Uint(U256::from(2))]);
+        let amounts = Token::Array(vec![Token::Uint(U256::from(10)), Token::Uint(U256::from(20))]);
+        let data = calldata(
+            "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+            &[Token::Address(from), Token::Address(to), ids, amounts, Token::Bytes(vec![0xca, 0xfe])],
+        );
+        let call = decode_calldata(&data).unwrap();
+        assert_eq!(call["standards"], json!(["erc1155"]));
+        assert_eq!(call["arguments"][2]["value"], json!(["1", "2"]));
+        assert_eq!(call["arguments"][4]["value"], "cafe");
+
+        let data = calldata(
+            "safeTransferFrom(address,address,uint256,bytes)",
+            &[Token::Address(from), Token::Address(to), Token::Uint(U256::from(7)), Token::Bytes(vec![])],
+        );
+        assert_eq!(decode_calldata(&data).unwrap()["arguments"][2]["name"], "tokenId");
+    }
+
+    #[test]
+    fn test_eip2612_permit() {
+        let permit = |value: U256, deadline: U256| {
+            calldata(
+                "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)",
+                &[
+                    Token::Address(Address::repeat_byte(0x11)),
+                    Token::Address(Address::repeat_byte(0x22)),
+                    Token::Uint(value),
+                    Token::Uint(deadline),
+                    Token::Uint(U256::from(27)),
+                    Token::FixedBytes(vec![1; 32]),
+                    Token::FixedBytes(vec![2; 32]),
+                ],
+            )
+        };
+        assert_eq!(hex::encode(&permit(U256::MAX, U256::from(1))[..4]), "d505accf");
+        let call = decode_calldata(&permit(U256::MAX, U256::from(1))).unwrap();
+        assert_eq!(call["arguments"][2]["name"], "value");
+        assert_eq!(call["unlimitedApproval"], true);
+        assert_eq!(decode_calldata(&permit(U256::from(1), U256::MAX)).unwrap()["unlimitedApproval"], false);
+    }
+
+    #[test]
+    fn test_dai_permit() {
+        // the third argument is the nonce, only `allowed` grants the allowance
+        let permit = |nonce: U256, allowed: bool| {
+            calldata(
+                "permit(address,address,uint256,uint256,bool,uint8,bytes32,bytes32)",
+                &[
+                    Token::Address(Address::repeat_byte(0x11)),
+                    Token::Address(Address::repeat_byte(0x22)),
+                    Token::Uint(nonce),
+                    Token::Uint(U256::MAX),
+                    Token::Bool(allowed),
+                    Token::Uint(U256::from(27)),
+                    Token::FixedBytes(vec![1; 32]),
+                    Token::FixedBytes(vec![2; 32]),
+                ],
+            )
+        };
+        assert_eq!(hex::encode(&permit(U256::zero(), true)[..4]), "8fcbaf0c");
+        let call = decode_calldata(&permit(U256::zero(), true)).unwrap();
+        assert_eq!(call["arguments"][4]["name"], "allowed");
+        assert_eq!(call["unlimitedApproval"], true);
+        assert_eq!(decode_calldata(&permit(U256::MAX, false)).unwrap()["unlimitedApproval"], false);
+    }
+
+    #[test]
+    fn test_registered_abi_cannot_override_standards() {
+        // sign_szabo_bytecode(bytes16,uint128) hashes to the selector of approve(address,uint256)
+        let abi = r#"[{"type": "function", "name": "sign_szabo_bytecode", "stateMutability": "nonpayable", "outputs": [],
+            "inputs": [{"name": "a", "type": "bytes16"}, {"name": "b", "type": "uint128"}]},
+            {"type": "function", "name": "vote", "stateMutability": "nonpayable", "outputs": [],
+            "inputs": [{"name": "proposalId", "type": "uint256"}]}]"#;
+        let error = register_abi(abi).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidArgument);
+        assert_eq!(
+            error.message(),
+            "sign_szabo_bytecode(bytes16,uint128) has the selector of approve(address,uint256)"
+        );
+        let vote = calldata("vote(uint256)", &[Token::Uint(U256::one())]);
+        assert_eq!(decode_calldata(&vote).err().map(|e| e.kind()), Some(ErrorKind::NotFound));
+        let approve = calldata("approve(address,uint256)", &[Token::Address(Address::zero()), Token::Uint(U256::MAX)]);
+        let call = decode_calldata(&approve).unwrap();
+        assert_eq!(call["function"], "approve");
+        assert_eq!(call["unlimitedApproval"], true);
+
+        // the standards declared again by a token ABI are kept as they are
+        let abi = r#"[{"type": "function", "name": "transfer", "stateMutability": "nonpayable", "outputs": [],
+            "inputs": [{"name": "recipient", "type": "address"}, {"name": "value", "type": "uint256"}]},
+            {"type": "function", "name": "mint", "stateMutability": "nonpayable", "outputs": [],
+            "inputs": [{"name": "to", "type": "address"}, {"name": "value", "type": "uint256"}]}]"#;
+        assert_eq!(register_abi(abi).unwrap(), 1);
+        let transfer = calldata("transfer(address,uint256)", &[Token::Address(Address::zero()), Token::Uint(U256::one())]);
+        let call = decode_calldata(&transfer).unwrap();
+        assert_eq!(call["standards"], json!(["erc20"]));
+        assert_eq!(call["arguments"][0]["name"], "to");
+    }
+
+    #[test]
+    fn test_registered_abi() {
+        let data = calldata("castVote(uint256,int8)", &[Token::Uint(U256::from(42)), Token::Int(U256::MAX)]);
+        assert_eq!(decode_calldata(&data).err().map(|e| e.kind()), Some(ErrorKind::NotFound));
+        let abi = r#"[{"type": "function", "name": "castVote", "stateMutability": "nonpayable", "outputs": [],
+            "inputs": [{"name": "proposalId", "type": "uint256"}, {"name": "support", "type": "int8"}]}]"#;
+        assert_eq!(register_abi(abi).unwrap(), 1);
+        let call = decode_calldata(&data).unwrap();
+        assert_eq!(call["function"], "castVote");
+        assert_eq!(call["standards"], json!([]));
+        assert_eq!(call["arguments"][1]["value"], "-1");
+
+        assert_eq!(register_abi("{").err().map(|e| e.kind()), Some(ErrorKind::InvalidArgument));
+        assert_eq!(decode_calldata(&data[..3]).err().map(|e| e.kind()), Some(ErrorKind::InvalidArgument));
+        let transfer = calldata("transfer(address,uint256)", &[Token::Address(Address::zero())]);
+        assert_eq!(decode_calldata(&transfer).err().map(|e| e.kind()), Some(ErrorKind::DecodeFailure));
+    }
+}
@@ -1,20 +1,38 @@
-use crate::response::{PtrResponse, Response};
-use crate::types::{PtrString, PtrVoid};
-use crate::utils::{convert_ptr_string_to_string, parse_ptr_string_to_bytes};
+use crate::error::{Error, ErrorKind};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::{PtrBytes, PtrString, PtrVoid};
+use crate::ur_encoder::{new_encoder, EncoderOptions, DEFAULT_MAX_FRAGMENT_LEN};
+use crate::utils::{convert_ptr_string_to_string, parse_ptr_bytes, parse_ptr_string_to_bytes};
 use ur_registry::crypto_key_path::CryptoKeyPath;
 use ur_registry::ethereum::eth_sign_request::{DataType, EthSignRequest};
 use ur_registry::traits::{To, RegistryItem};
 
+impl RegistryObject for EthSignRequest {
+    fn object_type() -> &'static str {
+        "eth-sign-request"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match EthSignRequest::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
 }
 
 #[no_mangle]
 pub extern "C" fn eth_sign_request_new() -> PtrResponse {
-    Response::success_object(Box::into_raw(Box::new(EthSignRequest::default())) as PtrVoid).c_ptr()
+    catch_panic(|| {
+        Response::success_object(EthSignRequest::default()).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn eth_sign_request_free(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<EthSignRequest>(eth_sign_request).c_ptr()
+    })
 }
 
 #[no_mangle]
@@ -28,34 +46,70 @@ pub extern "C" fn eth_sign_request_construct(
     address: PtrString,
     origin: PtrString,
 ) -> PtrResponse {
-    let request_id = match parse_ptr_string_to_bytes(request_id).map_err(|e| Response::error(e)) {
-        Ok(v) => v,
-        Err(e) => return e.c_ptr(),
-    };
-    let sign_data = match parse_ptr_string_to_bytes(sign_data).map_err(|e| Response::error(e)) {
+    catch_panic(|| {
+        let sign_data = match parse_ptr_string_to_bytes(sign_data).map_err(|e| Response::error(e.for_field("sign_data"))) {
+            Ok(v) => v,
+            Err(e) => return e.c_ptr(),
+        };
+        construct(request_id, sign_data, sign_type, chain_id, path, xfp, address, origin).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn eth_sign_request_construct_from_bytes(
+    request_id: PtrString,
+    sign_data: PtrBytes,
+    sign_data_len: usize,
+    sign_type: u32,
+    chain_id: u32,
+    path: PtrString,
+    xfp: u32,
+    address: PtrString,
+    origin: PtrString,
+) -> PtrResponse {
+    catch_panic(|| {
+        let sign_data = match parse_ptr_bytes(sign_data, sign_data_len).map_err(|e| Response::error(e.for_field("sign_data"))) {
+            Ok(v) => v,
+            Err(e) => return e.c_ptr(),
+        };
+        construct(request_id, sign_data, sign_type, chain_id, path, xfp, address, origin).c_ptr()
+    })
+}
+
+fn construct(
+    request_id: PtrString,
+    sign_data: Vec<u8>,
+    sign_type: u32,
+    chain_id: u32,
+    path: PtrString,
+    xfp: u32,
+    address: PtrString,
+    origin: PtrString,
+) -> Response {
+    let request_id = match parse_ptr_string_to_bytes(request_id).map_err(|e| Response::error(e.for_field("request_id"))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
-    let address = match parse_ptr_string_to_bytes(address).map_err(|e| Response::error(e)) {
+    let address = match parse_ptr_string_to_bytes(address).map_err(|e| Response::error(e.for_field("address"))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
-    let origin = match convert_ptr_string_to_string(origin).map_err(|e| Response::error(e)) {
+    let origin = match convert_ptr_string_to_string(origin).map_err(|e| Response::error(e.for_field("origin"))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
-    let data_type = match DataType::from_u32(sign_type).map_err(|e| Response::error(e)) {
+    let data_type = match DataType::from_u32(sign_type).map_err(|e| Response::error(Error::invalid_argument("sign_type", e))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
-    let path = match convert_ptr_string_to_string(path).map_err(|e| Response::error(e)) {
+    let path = match convert_ptr_string_to_string(path).map_err(|e| Response::error(e.for_field("path"))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
     let derivation_path = match CryptoKeyPath::from_path(path, Some(xfp.to_be_bytes()))
-        .map_err(|e| Response::error(e)) {
+        .map_err(|e| Response::error(Error::invalid_path("path", e))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
     let request = EthSignRequest::new(
         Some(request_id),
@@ -66,24 +120,141 @@ pub extern "C" fn eth_sign_request_construct(
         Some(address),
         Some(origin),
     );
-    Response::success_object(Box::into_raw(Box::new(request)) as PtrVoid).c_ptr()
+    Response::success_object(request)
 }
 
 #[no_mangle]
-pub extern "C" fn eth_sign_request_get_ur_encoder(eth_sign_request: &mut EthSignRequest) -> PtrResponse {
-    let message = eth_sign_request.to_bytes().unwrap();
-    let ur_encoder = ur::Encoder::new(
-        message.as_slice(),
-        400,
-        EthSignRequest::get_registry_type().get_type(),
-    )
-    .unwrap();
-    Response::success_object(Box::into_raw(Box::new(ur_encoder)) as PtrVoid).c_ptr()
+pub extern "C" fn eth_sign_request_get_ur_encoder(eth_sign_request: PtrVoid) -> PtrResponse {
+    eth_sign_request_get_ur_encoder_with_options(eth_sign_request, DEFAULT_MAX_FRAGMENT_LEN, 0, 0)
 }
 
 #[no_mangle]
-pub extern "C" fn eth_sign_request_get_request_id(eth_sign_request: &mut EthSignRequest) -> PtrResponse {
-    eth_sign_request.get_request_id().map_or(Response::success_null().c_ptr(), |id| {
-        Response::success_string(hex::encode(id)).c_ptr()
+pub extern "C" fn eth_sign_request_get_ur_encoder_with_options(
+    eth_sign_request: PtrVoid,
+    max_fragment_len: u32,
+    min_fragment_len: u32,
+    first_seq_num: u32,
+) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            let message = match eth_sign_request.to_bytes() {
+                Ok(v) => v,
+                Err(e) => return Response::error(Error::new(ErrorKind::CborError, e)),
+            };
+            match new_encoder(
+                message.as_slice(),
+                EthSignRequest::get_registry_type().get_type(),
+                EncoderOptions::new(max_fragment_len, min_fragment_len, first_seq_num),
+            ) {
+                Ok(ur_encoder) => Response::success_object(ur_encoder),
+                Err(e) => Response::error(e),
+            }
+        })
+        .c_ptr()
     })
-}
\ No newline at end of file
+}
+
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_request_id(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            eth_sign_request.get_request_id().map_or(Response::success_null(), |id| {
+                Response::success_string(hex::encode(id))
+            })
+        })
+        .c_ptr()
+    })
+}
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_sign_data(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            Response::success_string(hex::encode(eth_sign_request.get_sign_data()))
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_sign_data_bytes(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            Response::success_bytes(eth_sign_request.get_sign_data())
+        })
+        .c_ptr()
+    })
+}
+
+/// Same values as the `sign_type` accepted by `eth_sign_request_construct`: `1` legacy
+/// transaction, `2` EIP-712 typed data, `3` personal message, `4` typed transaction.
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_data_type(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            Response::success_uint32(eth_sign_request.get_data_type() as u32)
+        })
+        .c_ptr()
+    })
+}
+
+/// The chain id as a decimal string, since a decoded request may carry one that does not fit
+/// in 32 bits.
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_chain_id(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            eth_sign_request.get_chain_id().map_or(Response::success_null(), |chain_id| {
+                Response::success_string(chain_id.to_string())
+            })
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_derivation_path(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            match eth_sign_request.get_derivation_path().get_path() {
+                Some(path) => Response::success_string(path),
+                None => Response::success_null(),
+            }
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_source_fingerprint(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            match eth_sign_request.get_derivation_path().get_source_fingerprint() {
+                Some(fingerprint) => Response::success_string(hex::encode(fingerprint)),
+                None => Response::success_null(),
+            }
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_address(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            eth_sign_request.get_address().map_or(Response::success_null(), |address| {
+                Response::success_string(hex::encode(address))
+            })
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_origin(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            eth_sign_request.get_origin().map_or(Response::success_null(), Response::success_string)
+        })
+        .c_ptr()
+    })
+}
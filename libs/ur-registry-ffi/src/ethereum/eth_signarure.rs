@@ -1,24 +1,59 @@
+use crate::error::{Error, ErrorKind};
 use ur_registry::ethereum::eth_signature::EthSignature;
 use ur_registry::traits::From;
-use crate::response::{PtrResponse, Response};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
 use crate::types::PtrVoid;
 
+impl RegistryObject for EthSignature {
+    fn object_type() -> &'static str {
+        "eth-signature"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match EthSignature::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
 }
 
 #[no_mangle]
-pub extern "C" fn eth_signature_get_signature(eth_signature: &mut EthSignature) -> PtrResponse {
-    Response::success_string(hex::encode(eth_signature.get_signature())).c_ptr()
+pub extern "C" fn eth_signature_free(eth_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<EthSignature>(eth_signature).c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn eth_signature_get_request_id(eth_signature: &mut EthSignature) -> PtrResponse {
-    match eth_signature.get_request_id() {
-        Some(v) => Response::success_string(hex::encode(v)).c_ptr(),
-        None => Response::error(format!("No request id supplied")).c_ptr()
-    }
-}
\ No newline at end of file
+pub extern "C" fn eth_signature_get_signature(eth_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_signature, |eth_signature: &mut EthSignature| {
+            Response::success_string(hex::encode(eth_signature.get_signature()))
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn eth_signature_get_signature_bytes(eth_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_signature, |eth_signature: &mut EthSignature| {
+            Response::success_bytes(eth_signature.get_signature())
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn eth_signature_get_request_id(eth_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_signature, |eth_signature: &mut EthSignature| {
+            match eth_signature.get_request_id() {
+                Some(v) => Response::success_string(hex::encode(v)),
+                None => Response::error(Error::new(ErrorKind::NotFound, "No request id supplied"))
+            }
+        })
+        .c_ptr()
+    })
+}
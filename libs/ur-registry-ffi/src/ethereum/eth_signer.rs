@@ -0,0 +1,226 @@
+use super::eip712::TypedData;
+use super::keccak256;
+use crate::error::Error;
+use crate::handle::map_object;
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::PtrVoid;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, Secp256k1};
+use serde_json::json;
+use ur_registry::ethereum::eth_sign_request::{DataType, EthSignRequest};
+use ur_registry::ethereum::eth_signature::EthSignature;
+
+/// The hash that is signed for each `DataType`: the transaction itself for legacy and typed
+/// transactions, the EIP-191 prefixed message for personal messages and the EIP-712 digest for
+/// typed data.
+pub fn signing_hash(data_type: &DataType, sign_data: &[u8]) -> Result<[u8; 32], Error> {
+    match data_type {
+        DataType::Transaction | DataType::TypedTransaction => Ok(keccak256(sign_data)),
+        DataType::PersonalMessage => {
+            let mut message = format!("\x19Ethereum Signed Message:\n{}", sign_data.len()).into_bytes();
+            message.extend_from_slice(sign_data);
+            Ok(keccak256(&message))
+        }
+        DataType::TypedData => TypedData::parse(sign_data)?.digest(),
+    }
+}
+
+/// `r ‖ s ‖ v`, where `v` may span several bytes for EIP-155 signatures on large chain ids.
+pub struct Signature {
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+    pub v: u64,
+}
+
+impl Signature {
+    pub fn parse(signature: &[u8]) -> Result<Self, Error> {
+        if !(65..=72).contains(&signature.len()) {
+            return Err(Error::invalid_argument(
+                "signature",
+                format!("signature must be 65 to 72 bytes, got {}", signature.len()),
+            ));
+        }
+        Ok(Signature {
+            r: signature[..32].try_into().unwrap(),
+            s: signature[32..64].try_into().unwrap(),
+            v: signature[64..].iter().fold(0, |v, b| (v << 8) | *b as u64),
+        })
+    }
+
+    /// Accepts `0`/`1`, `27`/`28` and EIP-155 values of `v`.
+    pub fn recovery_id(&self) -> Result<u8, Error> {
+        match self.v {
+            0 | 1 => Ok(self.v as u8),
+            27 | 28 => Ok((self.v - 27) as u8),
+            v if v >= 35 => Ok(((v - 35) % 2) as u8),
+            v => Err(Error::invalid_argument("signature", format!("v value {} is invalid", v))),
+        }
+    }
+
+    /// The chain id an EIP-155 `v` commits to.
+    pub fn chain_id(&self) -> Option<u64> {
+        (self.v >= 35).then(|| (self.v - 35) / 2)
+    }
+
+    /// Rejects an EIP-155 signature made for another chain than the request.
+    pub fn check_chain_id(&self, chain_id: Option<i128>) -> Result<(), Error> {
+        match (self.chain_id(), chain_id) {
+            (Some(signed), Some(expected)) if signed as i128 != expected => Err(Error::invalid_argument(
+                "signature",
+                format!("signature was made for chain id {}, not {}", signed, expected),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn recover(&self, hash: &[u8; 32]) -> Result<PublicKey, Error> {
+        let invalid = |e: secp256k1::Error| Error::invalid_argument("signature", e);
+        let recovery_id = RecoveryId::from_i32(self.recovery_id()? as i32).map_err(invalid)?;
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(&self.r);
+        compact[32..].copy_from_slice(&self.s);
+        let signature = RecoverableSignature::from_compact(&compact, recovery_id).map_err(invalid)?;
+        let message = Message::from_slice(hash).map_err(invalid)?;
+        Secp256k1::verification_only()
+            .recover_ecdsa(&message, &signature)
+            .map_err(invalid)
+    }
+}
+
+pub fn address(public_key: &PublicKey) -> [u8; 20] {
+    keccak256(&public_key.serialize_uncompressed()[1..])[12..].try_into().unwrap()
+}
+
+/// Recovers who signed `eth_signature` for `eth_sign_request` and checks it against the address
+/// the request was made for, returning
+/// `{"hash": <hex>, "publicKey": <hex>, "address": <hex>, "addressMatches": true}`.
+/// `addressMatches` is `null` when the request does not carry an address. An EIP-155 `v` has to
+/// commit to the chain id of the request.
+#[no_mangle]
+pub extern "C" fn eth_signature_recover_signer(eth_signature: PtrVoid, eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        let (signature, signature_request_id) =
+            match map_object(eth_signature, |s: &mut EthSignature| (s.get_signature(), s.get_request_id())) {
+                Ok(v) => v,
+                Err(e) => return e.c_ptr(),
+            };
+        let (sign_data, data_type, chain_id, expected_address, request_id) =
+            match map_object(eth_sign_request, |r: &mut EthSignRequest| {
+                (r.get_sign_data(), r.get_data_type(), r.get_chain_id(), r.get_address(), r.get_request_id())
+            }) {
+                Ok(v) => v,
+                Err(e) => return e.c_ptr(),
+            };
+        if let (Some(a), Some(b)) = (&signature_request_id, &request_id) {
+            if a != b {
+                return Response::error(Error::invalid_argument(
+                    "eth_signature",
+                    "signature was made for another request",
+                ))
+                .c_ptr();
+            }
+        }
+        let hash = match signing_hash(&data_type, &sign_data) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("sign_data")).c_ptr(),
+        };
+        let signature = match Signature::parse(&signature) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e).c_ptr(),
+        };
+        let public_key = match signature.check_chain_id(chain_id).and_then(|_| signature.recover(&hash)) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e).c_ptr(),
+        };
+        let address = address(&public_key);
+        let address_matches = expected_address.map(|expected| expected == address);
+        Response::success_string(
+            json!({
+                "hash": hex::encode(hash),
+                "publicKey": hex::encode(public_key.serialize_uncompressed()),
+                "address": hex::encode(address),
+                "addressMatches": address_matches,
+            })
+            .to_string(),
+        )
+        .c_ptr()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::ethereum::eip712::MAIL_TYPED_DATA;
+    use secp256k1::SecretKey;
+
+    const PRIVATE_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const ADDRESS: &str = "2c7536e3605d9c16a7a3d7b1898e529396a65c23";
+
+    fn sign(hash: &[u8; 32], v_offset: u64) -> Vec<u8> {
+        let secret_key = SecretKey::from_slice(&hex::decode(PRIVATE_KEY).unwrap()).unwrap();
+        let signature = Secp256k1::new().sign_ecdsa_recoverable(&Message::from_slice(hash).unwrap(), &secret_key);
+        let (recovery_id, compact) = signature.serialize_compact();
+        let v = recovery_id.to_i32() as u64 + v_offset;
+        let mut signature = compact.to_vec();
+        signature.extend(v.to_be_bytes().iter().skip_while(|b| **b == 0));
+        if v == 0 {
+            signature.push(0);
+        }
+        signature
+    }
+
+    fn recover(signature: &[u8], hash: &[u8; 32]) -> String {
+        hex::encode(address(&Signature::parse(signature).unwrap().recover(hash).unwrap()))
+    }
+
+    #[test]
+    fn test_personal_message_hash() {
+        assert_eq!(
+            hex::encode(signing_hash(&DataType::PersonalMessage, b"Hello World").unwrap()),
+            "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2"
+        );
+    }
+
+    #[test]
+    fn test_typed_data_hash() {
+        assert_eq!(
+            hex::encode(signing_hash(&DataType::TypedData, MAIL_TYPED_DATA.as_bytes()).unwrap()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+        let error = signing_hash(&DataType::TypedData, b"{}").err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidArgument);
+    }
+
+    #[test]
+    fn test_recover_for_every_v_encoding() {
+        let hash = signing_hash(&DataType::Transaction, &[0xc0]).unwrap();
+        // plain, Bitcoin style and EIP-155 for mainnet and Sepolia
+        for v_offset in [0, 27, 37, 22_310_257] {
+            assert_eq!(recover(&sign(&hash, v_offset), &hash), ADDRESS);
+        }
+        let mut signature = sign(&hash, 27);
+        signature[64] = 30;
+        let error = Signature::parse(&signature).and_then(|s| s.recovery_id()).err();
+        assert_eq!(error.map(|e| e.kind()), Some(ErrorKind::InvalidArgument));
+        assert!(Signature::parse(&signature[..64]).is_err());
+        let other = signing_hash(&DataType::Transaction, &[0xc1]).unwrap();
+        assert_ne!(recover(&sign(&hash, 27), &other), ADDRESS);
+    }
+
+    #[test]
+    fn test_eip155_chain_id() {
+        let hash = signing_hash(&DataType::Transaction, &[0xc0]).unwrap();
+        let signature = |v_offset| Signature::parse(&sign(&hash, v_offset)).unwrap();
+        assert_eq!(signature(27).chain_id(), None);
+        assert_eq!(signature(37).chain_id(), Some(1));
+        assert_eq!(signature(22_310_257).chain_id(), Some(11_155_111));
+
+        assert!(signature(27).check_chain_id(Some(1)).is_ok());
+        assert!(signature(37).check_chain_id(Some(1)).is_ok());
+        assert!(signature(37).check_chain_id(None).is_ok());
+        let error = signature(37).check_chain_id(Some(11_155_111)).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidArgument);
+        assert!(signature(22_310_257).check_chain_id(Some(1)).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use super::eip712::{TypedData, DOMAIN_TYPE};
+use super::eth_abi::decode_calldata;
+use super::eth_transaction::{invalid_transaction, split_transaction, EIP1559_TX_TYPE, EIP2930_TX_TYPE};
+use super::format_uint;
+use crate::error::Error;
+use crate::handle::with_object;
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::PtrVoid;
+use rlp::Rlp;
+use serde_json::{json, Map, Value};
+use ur_registry::ethereum::eth_sign_request::{DataType, EthSignRequest};
+
+#[derive(Clone, Copy)]
+enum Field {
+    Uint(&'static str),
+    Address(&'static str),
+    Bytes(&'static str),
+    AccessList,
+}
+
+/// The trailing `0, 0` that EIP-155 puts in place of `r` and `s` are not shown.
+const LEGACY_FIELDS: [Field; 7] = [
+    Field::Uint("nonce"),
+    Field::Uint("gasPrice"),
+    Field::Uint("gasLimit"),
+    Field::Address("to"),
+    Field::Uint("value"),
+    Field::Bytes("data"),
+    Field::Uint("chainId"),
+];
+
+const EIP2930_FIELDS: [Field; 8] = [
+    Field::Uint("chainId"),
+    Field::Uint("nonce"),
+    Field::Uint("gasPrice"),
+    Field::Uint("gasLimit"),
+    Field::Address("to"),
+    Field::Uint("value"),
+    Field::Bytes("data"),
+    Field::AccessList,
+];
+
+const EIP1559_FIELDS: [Field; 9] = [
+    Field::Uint("chainId"),
+    Field::Uint("nonce"),
+    Field::Uint("maxPriorityFeePerGas"),
+    Field::Uint("maxFeePerGas"),
+    Field::Uint("gasLimit"),
+    Field::Address("to"),
+    Field::Uint("value"),
+    Field::Bytes("data"),
+    Field::AccessList,
+];
+
+fn data(item: &Rlp) -> Result<Vec<u8>, Error> {
+    if item.is_list() {
+        return Err(invalid_transaction("unexpected list"));
+    }
+    item.data().map(|v| v.to_vec()).map_err(invalid_transaction)
+}
+
+fn access_list(item: &Rlp) -> Result<Value, Error> {
+    item.iter()
+        .map(|entry| {
+            let keys = entry
+                .at(1)
+                .map_err(invalid_transaction)?
+                .iter()
+                .map(|key| data(&key).map(hex::encode))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(json!({
+                "address": hex::encode(data(&entry.at(0).map_err(invalid_transaction)?)?),
+                "storageKeys": keys,
+            }))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+/// Integers are decimal strings, `to` is `null` for contract creation and `chainId` is `null` for
+/// legacy transactions signed without one. `call` holds the decoded `data` of calls to known
+/// functions, see `decode_calldata`.
+fn summarize_transaction(data_type: &DataType, sign_data: &[u8]) -> Result<Value, Error> {
+    let (tx_type, fields) = split_transaction(data_type, sign_data)?;
+    let (name, layout): (&str, &[Field]) = match tx_type {
+        None => ("legacy", &LEGACY_FIELDS),
+        Some(EIP2930_TX_TYPE) => ("eip2930", &EIP2930_FIELDS),
+        Some(EIP1559_TX_TYPE) => ("eip1559", &EIP1559_FIELDS),
+        Some(v) => return Err(invalid_transaction(format!("type {}", v))),
+    };
+    let mut summary = Map::new();
+    summary.insert("dataType".to_string(), json!("transaction"));
+    summary.insert("transactionType".to_string(), json!(name));
+    summary.insert("chainId".to_string(), Value::Null);
+    let mut calldata = vec![];
+    let count = fields.item_count().map_err(invalid_transaction)?;
+    for (i, field) in layout.iter().enumerate().take(count) {
+        let item = fields.at(i).map_err(invalid_transaction)?;
+        let (key, value) = match field {
+            Field::Uint(key) => (*key, json!(format_uint(&data(&item)?))),
+            Field::Address(key) => match data(&item)? {
+                v if v.is_empty() => (*key, Value::Null),
+                v if v.len() == 20 => (*key, json!(hex::encode(v))),
+                _ => return Err(invalid_transaction("address must be 20 bytes")),
+            },
+            Field::Bytes(key) => {
+                calldata = data(&item)?;
+                (*key, json!(hex::encode(&calldata)))
+            }
+            Field::AccessList => ("accessList", access_list(&item)?),
+        };
+        summary.insert(key.to_string(), value);
+    }
+    let call = match summary.get("to") {
+        Some(Value::String(_)) => decode_calldata(&calldata).unwrap_or(Value::Null),
+        _ => Value::Null,
+    };
+    summary.insert("call".to_string(), call);
+    Ok(Value::Object(summary))
+}
+
+fn summarize_personal_message(sign_data: &[u8]) -> Value {
+    json!({
+        "dataType": "personalMessage",
+        "utf8": std::str::from_utf8(sign_data).ok(),
+        "hex": hex::encode(sign_data),
+    })
+}
+
+fn summarize_typed_data(sign_data: &[u8]) -> Result<Value, Error> {
+    let typed_data = TypedData::parse(sign_data)?;
+    // only typed data that can be signed is described
+    typed_data.digest()?;
+    let message = match typed_data.primary_type.as_str() {
+        DOMAIN_TYPE => Value::Null,
+        primary_type => typed_data.describe("message", primary_type, &typed_data.message)?,
+    };
+    Ok(json!({
+        "dataType": "typedData",
+        "primaryType": typed_data.primary_type,
+        "domain": typed_data.describe("domain", DOMAIN_TYPE, &typed_data.domain)?,
+        "message": message,
+    }))
+}
+
+pub fn summarize(data_type: &DataType, sign_data: &[u8]) -> Result<Value, Error> {
+    match data_type {
+        DataType::Transaction | DataType::TypedTransaction => summarize_transaction(data_type, sign_data),
+        DataType::PersonalMessage => Ok(summarize_personal_message(sign_data)),
+        DataType::TypedData => summarize_typed_data(sign_data),
+    }
+}
+
+/// Describes what the request asks to sign, as a JSON object whose `dataType` is one of
+/// `transaction`, `personalMessage` or `typedData`:
+///
+/// * transactions list their RLP fields under their usual names, e.g. `nonce`, `maxFeePerGas`
+///   or `accessList`, along with a `transactionType` of `legacy`, `eip2930` or `eip1559` and the
+///   decoded `call`, if any
+/// * personal messages carry `hex` and, when the message is valid UTF-8, `utf8`
+/// * typed data carries its `primaryType` and `domain` and `message` trees as built by
+///   `TypedData::describe`
+#[no_mangle]
+pub extern "C" fn eth_sign_request_get_summary(eth_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(eth_sign_request, |eth_sign_request: &mut EthSignRequest| {
+            match summarize(&eth_sign_request.get_data_type(), &eth_sign_request.get_sign_data()) {
+                Ok(summary) => Response::success_string(summary.to_string()),
+                Err(e) => Response::error(e.for_field("sign_data")),
+            }
+        })
+        .c_ptr()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use rlp::RlpStream;
+
+    #[test]
+    fn test_legacy_transaction() {
+        let sign_data = hex::decode(
+            "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080",
+        )
+        .unwrap();
+        assert_eq!(
+            summarize(&DataType::Transaction, &sign_data).unwrap(),
+            json!({
+                "dataType": "transaction",
+                "transactionType": "legacy",
+                "nonce": "9",
+                "gasPrice": "20000000000",
+                "gasLimit": "21000",
+                "to": "3535353535353535353535353535353535353535",
+                "value": "1000000000000000000",
+                "data": "",
+                "chainId": "1",
+                "call": null,
+            })
+        );
+
+        let mut pre_eip155 = RlpStream::new_list(6);
+        pre_eip155.append(&0u64).append(&1u64).append(&21_000u64).append(&"".as_bytes());
+        pre_eip155.append(&0u64).append(&vec![0x60u8, 0x80]);
+        let summary = summarize(&DataType::Transaction, &pre_eip155.out()).unwrap();
+        assert_eq!(summary["to"], Value::Null);
+        assert_eq!(summary["chainId"], Value::Null);
+        assert_eq!(summary["data"], "6080");
+        assert_eq!(summary["call"], Value::Null);
+    }
+
+    #[test]
+    fn test_eip1559_transaction() {
+        let mut fields = RlpStream::new_list(9);
+        fields.append(&137u64).append(&3u64).append(&1_500_000_000u64).append(&30_000_000_000u64);
+        fields.append(&60_000u64).append(&[0x35u8; 20].as_slice()).append(&0u64);
+        let transfer = format!("a9059cbb{}{:064x}", "00".repeat(12) + &"11".repeat(20), 1_000_000u64);
+        fields.append(&hex::decode(&transfer).unwrap());
+        fields.begin_list(1);
+        fields.begin_list(2).append(&[0x11u8; 20].as_slice());
+        fields.begin_list(1).append(&[0x22u8; 32].as_slice());
+        let mut sign_data = vec![EIP1559_TX_TYPE];
+        sign_data.extend_from_slice(&fields.out());
+        let summary = summarize(&DataType::TypedTransaction, &sign_data).unwrap();
+        assert_eq!(summary["transactionType"], "eip1559");
+        assert_eq!(summary["chainId"], "137");
+        assert_eq!(summary["maxPriorityFeePerGas"], "1500000000");
+        assert_eq!(summary["maxFeePerGas"], "30000000000");
+        assert_eq!(summary["gasLimit"], "60000");
+        assert_eq!(summary["value"], "0");
+        assert_eq!(summary["data"], transfer);
+        assert_eq!(summary["call"]["function"], "transfer");
+        assert_eq!(summary["call"]["arguments"][1]["value"], "1000000");
+        assert_eq!(
+            summary["accessList"],
+            json!([{"address": "11".repeat(20), "storageKeys": ["22".repeat(32)]}])
+        );
+        let error = summarize(&DataType::TypedTransaction, &sign_data[..20]).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::DecodeFailure);
+    }
+
+    #[test]
+    fn test_personal_message_and_typed_data() {
+        assert_eq!(
+            summarize(&DataType::PersonalMessage, b"Hello").unwrap(),
+            json!({"dataType": "personalMessage", "utf8": "Hello", "hex": "48656c6c6f"})
+        );
+        assert_eq!(summarize(&DataType::PersonalMessage, &[0xff, 0x00]).unwrap()["utf8"], Value::Null);
+
+        let typed_data = br#"{
+            "types": {
+                "EIP712Domain": [{"name": "name", "type": "string"}, {"name": "chainId", "type": "uint256"}],
+                "Group": [{"name": "members", "type": "Person[]"}],
+                "Person": [{"name": "name", "type": "string"}, {"name": "wallet", "type": "address"}]
+            },
+            "primaryType": "Group",
+            "domain": {"name": "Ether Mail", "chainId": 1},
+            "message": {"members": [{"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"}]}
+        }"#;
+        let summary = summarize(&DataType::TypedData, typed_data).unwrap();
+        assert_eq!(summary["primaryType"], "Group");
+        assert_eq!(
+            summary["domain"]["children"],
+            json!([
+                {"name": "name", "type": "string", "value": "Ether Mail"},
+                {"name": "chainId", "type": "uint256", "value": 1},
+            ])
+        );
+        let member = &summary["message"]["children"][0]["children"][0];
+        assert_eq!(member["name"], "0");
+        assert_eq!(member["type"], "Person");
+        assert_eq!(member["children"][0], json!({"name": "name", "type": "string", "value": "Bob"}));
+        let error = summarize(&DataType::TypedData, br#"{"types": {}, "primaryType": "Mail"}"#).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidArgument);
+    }
+}
@@ -0,0 +1,252 @@
+use super::eth_signer::Signature;
+use super::keccak256;
+use crate::error::{Error, ErrorKind};
+use crate::handle::map_object;
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::PtrVoid;
+use rlp::{Rlp, RlpStream};
+use serde_json::json;
+use ur_registry::ethereum::eth_sign_request::{DataType, EthSignRequest};
+use ur_registry::ethereum::eth_signature::EthSignature;
+
+pub const EIP2930_TX_TYPE: u8 = 0x01;
+pub const EIP1559_TX_TYPE: u8 = 0x02;
+
+pub fn invalid_transaction(error: impl ToString) -> Error {
+    Error::new(ErrorKind::DecodeFailure, format!("transaction is invalid: {}", error.to_string()))
+}
+
+/// Big-endian integer without leading zeros, as RLP encodes integers.
+fn trim_integer(bytes: &[u8]) -> &[u8] {
+    &bytes[bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len())..]
+}
+
+/// Splits the sign data of a typed transaction into its type and the RLP list of its fields,
+/// legacy transactions having no type.
+pub fn split_transaction<'a>(data_type: &DataType, sign_data: &'a [u8]) -> Result<(Option<u8>, Rlp<'a>), Error> {
+    let (tx_type, payload) = match data_type {
+        DataType::Transaction => (None, sign_data),
+        DataType::TypedTransaction => match sign_data.split_first() {
+            Some((tx_type, payload)) if *tx_type == EIP2930_TX_TYPE || *tx_type == EIP1559_TX_TYPE => {
+                (Some(*tx_type), payload)
+            }
+            Some((tx_type, _)) => {
+                return Err(Error::new(
+                    ErrorKind::UnsupportedType,
+                    format!("transaction type {} is not supported", tx_type),
+                ))
+            }
+            None => return Err(invalid_transaction("empty sign data")),
+        },
+        _ => return Err(Error::new(ErrorKind::UnsupportedType, "sign data is not a transaction")),
+    };
+    let rlp = Rlp::new(payload);
+    let info = rlp.payload_info().map_err(invalid_transaction)?;
+    if !rlp.is_list() || info.header_len + info.value_len != payload.len() {
+        return Err(invalid_transaction("not a single RLP list"));
+    }
+    let expected = match tx_type {
+        None => &[6, 9][..],
+        Some(EIP2930_TX_TYPE) => &[8][..],
+        _ => &[9][..],
+    };
+    let count = rlp.item_count().map_err(invalid_transaction)?;
+    if !expected.contains(&count) {
+        return Err(invalid_transaction(format!("unexpected field count {}", count)));
+    }
+    Ok((tx_type, rlp))
+}
+
+/// Appends `v`, `r` and `s` to the unsigned transaction. Legacy transactions that carry a chain
+/// id get its EIP-155 `v`, `chain_id` of the request has to match it when given.
+pub fn sign_transaction(
+    data_type: &DataType,
+    sign_data: &[u8],
+    chain_id: Option<i128>,
+    signature: &Signature,
+) -> Result<Vec<u8>, Error> {
+    let (tx_type, fields) = split_transaction(data_type, sign_data)?;
+    let count = fields.item_count().map_err(invalid_transaction)?;
+    let recovery_id = signature.recovery_id()? as u64;
+    let (unsigned_count, v) = match tx_type {
+        Some(_) => (count, recovery_id),
+        None if count == 6 => (6, 27 + recovery_id),
+        None => {
+            let tx_chain_id = fields.val_at::<u64>(6).map_err(invalid_transaction)?;
+            // EIP-155 signs over `chain_id, 0, 0`, anything else in the placeholders is not a
+            // transaction a wallet would have signed
+            for i in 7..9 {
+                let field = fields.at(i).map_err(invalid_transaction)?;
+                if !field.is_data() || !field.is_empty() {
+                    return Err(invalid_transaction(format!("field {} must be empty", i)));
+                }
+            }
+            if let Some(chain_id) = chain_id.filter(|v| *v != tx_chain_id as i128) {
+                return Err(Error::invalid_argument(
+                    "chain_id",
+                    format!("chain id {} does not match the transaction chain id {}", chain_id, tx_chain_id),
+                ));
+            }
+            if tx_chain_id > (u64::MAX - 36) / 2 {
+                return Err(invalid_transaction(format!("chain id {} is too large", tx_chain_id)));
+            }
+            (6, tx_chain_id * 2 + 35 + recovery_id)
+        }
+    };
+    let mut stream = RlpStream::new_list(unsigned_count + 3);
+    for i in 0..unsigned_count {
+        stream.append_raw(fields.at(i).map_err(invalid_transaction)?.as_raw(), 1);
+    }
+    stream.append(&v);
+    stream.append(&trim_integer(&signature.r));
+    stream.append(&trim_integer(&signature.s));
+    let mut raw = tx_type.map(|v| vec![v]).unwrap_or_default();
+    raw.extend_from_slice(&stream.out());
+    Ok(raw)
+}
+
+/// Merges `eth_signature` into the transaction of `eth_sign_request`, returning
+/// `{"rawTransaction": <hex>, "hash": <hex>}` ready to be broadcast.
+#[no_mangle]
+pub extern "C" fn eth_signature_build_signed_transaction(
+    eth_signature: PtrVoid,
+    eth_sign_request: PtrVoid,
+) -> PtrResponse {
+    catch_panic(|| {
+        let signature = match map_object(eth_signature, |s: &mut EthSignature| s.get_signature()) {
+            Ok(v) => v,
+            Err(e) => return e.c_ptr(),
+        };
+        let (sign_data, data_type, chain_id) = match map_object(eth_sign_request, |r: &mut EthSignRequest| {
+            (r.get_sign_data(), r.get_data_type(), r.get_chain_id())
+        }) {
+            Ok(v) => v,
+            Err(e) => return e.c_ptr(),
+        };
+        let signature = match Signature::parse(&signature) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e).c_ptr(),
+        };
+        match sign_transaction(&data_type, &sign_data, chain_id, &signature) {
+            Ok(raw) => Response::success_string(
+                json!({"rawTransaction": hex::encode(&raw), "hash": hex::encode(keccak256(&raw))}).to_string(),
+            ),
+            Err(e) if e.kind() == ErrorKind::InvalidArgument => Response::error(e),
+            Err(e) => Response::error(e.for_field("sign_data")),
+        }
+        .c_ptr()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethereum::eth_signer::address;
+    use secp256k1::{Message, Secp256k1, SecretKey};
+
+    fn sign(hash: &[u8; 32], private_key: &[u8]) -> Signature {
+        let secret_key = SecretKey::from_slice(private_key).unwrap();
+        let signature = Secp256k1::new().sign_ecdsa_recoverable(&Message::from_slice(hash).unwrap(), &secret_key);
+        let (recovery_id, compact) = signature.serialize_compact();
+        let mut signature = compact.to_vec();
+        signature.push(recovery_id.to_i32() as u8);
+        Signature::parse(&signature).unwrap()
+    }
+
+    #[test]
+    fn test_eip155_transaction() {
+        // the example of EIP-155
+        let sign_data = hex::decode(
+            "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080",
+        )
+        .unwrap();
+        let hash = keccak256(&sign_data);
+        assert_eq!(hex::encode(hash), "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53");
+        let signature = sign(&hash, &[0x46; 32]);
+        let raw = sign_transaction(&DataType::Transaction, &sign_data, Some(1), &signature).unwrap();
+        assert_eq!(
+            hex::encode(raw),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340b\
+             d939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc6\
+             4214b297fb1966a3b6d83"
+        );
+        let raw = sign_transaction(&DataType::Transaction, &sign_data, None, &signature).unwrap();
+        assert_eq!(Rlp::new(&raw).val_at::<u64>(6).unwrap(), 37);
+
+        // the chain id signed over wins, a request for another chain is rejected
+        let error = sign_transaction(&DataType::Transaction, &sign_data, Some(5), &signature).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidArgument);
+
+        // the `r` and `s` placeholders have to be empty
+        let fields = Rlp::new(&sign_data);
+        let placeholders = [
+            (7, rlp::encode(&1u64)),
+            (8, rlp::encode(&[0x35u8; 32].as_slice())),
+            (7, rlp::encode_list::<u64, u64>(&[])),
+        ];
+        for (i, placeholder) in placeholders {
+            let mut tampered = RlpStream::new_list(9);
+            for j in 0..9 {
+                if j == i {
+                    tampered.append_raw(&placeholder, 1);
+                } else {
+                    tampered.append_raw(fields.at(j).unwrap().as_raw(), 1);
+                }
+            }
+            let error = sign_transaction(&DataType::Transaction, &tampered.out(), Some(1), &signature).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::DecodeFailure);
+        }
+    }
+
+    #[test]
+    fn test_typed_transactions() {
+        let mut fields = RlpStream::new_list(9);
+        fields.append(&1u64).append(&3u64).append(&1_000_000_000u64).append(&100_000_000_000u64);
+        fields.append(&21_000u64).append(&[0x35u8; 20].as_slice()).append(&1u64).append(&"".as_bytes());
+        fields.begin_list(0);
+        let mut sign_data = vec![EIP1559_TX_TYPE];
+        sign_data.extend_from_slice(&fields.out());
+        let private_key = [0x46; 32];
+        let signature = sign(&keccak256(&sign_data), &private_key);
+        let raw = sign_transaction(&DataType::TypedTransaction, &sign_data, Some(1), &signature).unwrap();
+        assert_eq!(raw[0], EIP1559_TX_TYPE);
+        let signed = Rlp::new(&raw[1..]);
+        assert_eq!(signed.item_count().unwrap(), 12);
+        assert_eq!(signed.val_at::<u64>(9).unwrap(), signature.recovery_id().unwrap() as u64);
+        let r: Vec<u8> = signed.val_at(10).unwrap();
+        assert_eq!(r, trim_integer(&signature.r));
+
+        // the signer can be recovered from the unsigned payload of the signed transaction
+        let mut unsigned = RlpStream::new_list(9);
+        for i in 0..9 {
+            unsigned.append_raw(signed.at(i).unwrap().as_raw(), 1);
+        }
+        let mut payload = vec![EIP1559_TX_TYPE];
+        payload.extend_from_slice(&unsigned.out());
+        assert_eq!(payload, sign_data);
+        let public_key = secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &SecretKey::from_slice(&private_key).unwrap());
+        assert_eq!(address(&signature.recover(&keccak256(&payload)).unwrap()), address(&public_key));
+
+        let mut access_list_tx = RlpStream::new_list(8);
+        access_list_tx.append(&1u64).append(&0u64).append(&1u64).append(&21_000u64);
+        access_list_tx.append(&"".as_bytes()).append(&0u64).append(&"".as_bytes());
+        access_list_tx.begin_list(0);
+        let mut sign_data = vec![EIP2930_TX_TYPE];
+        sign_data.extend_from_slice(&access_list_tx.out());
+        let raw = sign_transaction(&DataType::TypedTransaction, &sign_data, None, &signature).unwrap();
+        assert_eq!(Rlp::new(&raw[1..]).item_count().unwrap(), 11);
+    }
+
+    #[test]
+    fn test_rejects_non_transactions() {
+        let signature = sign(&[1; 32], &[0x46; 32]);
+        let kind = |data_type: DataType, sign_data: &[u8]| {
+            sign_transaction(&data_type, sign_data, Some(1), &signature).err().map(|e| e.kind())
+        };
+        assert_eq!(kind(DataType::PersonalMessage, b"hello"), Some(ErrorKind::UnsupportedType));
+        assert_eq!(kind(DataType::TypedTransaction, &[0x03, 0xc0]), Some(ErrorKind::UnsupportedType));
+        assert_eq!(kind(DataType::TypedTransaction, &[]), Some(ErrorKind::DecodeFailure));
+        assert_eq!(kind(DataType::Transaction, &[0xc2, 0x01, 0x02]), Some(ErrorKind::DecodeFailure));
+        assert_eq!(kind(DataType::Transaction, &[0x05]), Some(ErrorKind::DecodeFailure));
+    }
+}
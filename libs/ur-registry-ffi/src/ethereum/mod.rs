@@ -1,2 +1,33 @@
+use sha3::{Digest, Keccak256};
+
+pub mod eip712;
+pub mod eth_abi;
 pub mod eth_sign_request;
-pub mod eth_signarure;
\ No newline at end of file
+pub mod eth_signarure;
+pub mod eth_signer;
+pub mod eth_summary;
+pub mod eth_transaction;
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// Decimal representation of a big-endian unsigned integer of any length.
+pub fn format_uint(bytes: &[u8]) -> String {
+    let mut value = bytes.to_vec();
+    let mut digits = vec![];
+    while value.iter().any(|b| *b != 0) {
+        let mut remainder = 0u32;
+        for byte in value.iter_mut() {
+            let current = (remainder << 8) | *byte as u32;
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
@@ -1,45 +1,78 @@
-use crate::response::{PtrResponse, Response};
+use crate::error::{Error, ErrorKind};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
 use crate::types::PtrVoid;
 use ur_registry::extend::crypto_multi_accounts::CryptoMultiAccounts;
 use ur_registry::traits::From;
 
+impl RegistryObject for CryptoMultiAccounts {
+    fn object_type() -> &'static str {
+        "crypto-multi-accounts"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match CryptoMultiAccounts::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
 }
 
+#[no_mangle]
+pub extern "C" fn extend_crypto_multi_accounts_free(crypto_multi_accounts: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<CryptoMultiAccounts>(crypto_multi_accounts).c_ptr()
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn extend_crypto_multi_accounts_get_master_fingerprint(
-    crypto_multi_accounts: &mut CryptoMultiAccounts,
+    crypto_multi_accounts: PtrVoid,
 ) -> PtrResponse {
-    Response::success_string(hex::encode(crypto_multi_accounts.get_master_fingerprint())).c_ptr()
+    catch_panic(|| {
+        with_object(crypto_multi_accounts, |crypto_multi_accounts: &mut CryptoMultiAccounts| {
+            Response::success_string(hex::encode(crypto_multi_accounts.get_master_fingerprint()))
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn extend_crypto_multi_accounts_get_device(
-    crypto_multi_accounts: &mut CryptoMultiAccounts,
+    crypto_multi_accounts: PtrVoid,
 ) -> PtrResponse {
-    Response::success_string(crypto_multi_accounts.get_device().unwrap_or("".to_string())).c_ptr()
+    catch_panic(|| {
+        with_object(crypto_multi_accounts, |crypto_multi_accounts: &mut CryptoMultiAccounts| {
+            Response::success_string(crypto_multi_accounts.get_device().unwrap_or("".to_string()))
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn extend_crypto_multi_accounts_get_keys_len(
-    crypto_multi_accounts: &mut CryptoMultiAccounts,
+    crypto_multi_accounts: PtrVoid,
 ) -> PtrResponse {
-    Response::success_uint32(crypto_multi_accounts.get_keys().len() as u32).c_ptr()
+    catch_panic(|| {
+        with_object(crypto_multi_accounts, |crypto_multi_accounts: &mut CryptoMultiAccounts| {
+            Response::success_uint32(crypto_multi_accounts.get_keys().len() as u32)
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn extend_crypto_multi_accounts_get_key(
-    crypto_multi_accounts: &mut CryptoMultiAccounts,
+    crypto_multi_accounts: PtrVoid,
     index: u32,
 ) -> PtrResponse {
-    match crypto_multi_accounts.get_keys().get(index as usize) {
-        Some(key) => {
-            Response::success_object(Box::into_raw(Box::new(key.clone())) as PtrVoid).c_ptr()
-        }
-        None => Response::error(format!("No key for index {} was found", index)).c_ptr(),
-    }
+    catch_panic(|| {
+        with_object(crypto_multi_accounts, |crypto_multi_accounts: &mut CryptoMultiAccounts| {
+            match crypto_multi_accounts.get_keys().get(index as usize) {
+                Some(key) => Response::success_object(key.clone()),
+                None => Response::error(Error::invalid_argument("index", format!("No key for index {} was found", index))),
+            }
+        })
+        .c_ptr()
+    })
 }
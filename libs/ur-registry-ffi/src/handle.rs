@@ -0,0 +1,152 @@
+use crate::error::{Error, ErrorKind};
+use crate::response::Response;
+use crate::types::PtrVoid;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+/// Objects handed to the host are kept in a process wide registry and the host only ever sees an
+/// opaque id in place of a pointer. Every lookup checks the registry type recorded for the id, so
+/// a stale handle or a handle of the wrong type yields an error instead of undefined behaviour.
+pub trait RegistryObject: Any + Send {
+    fn object_type() -> &'static str
+    where
+        Self: Sized;
+}
+
+type SharedObject = Arc<Mutex<Box<dyn Any + Send>>>;
+
+struct Entry {
+    object_type: &'static str,
+    object: SharedObject,
+}
+
+#[derive(Default)]
+struct Registry {
+    last_id: usize,
+    entries: HashMap<usize, Entry>,
+}
+
+fn registry() -> MutexGuard<'static, Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY
+        .get_or_init(|| Mutex::new(Registry::default()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn register<T: RegistryObject>(object: T) -> PtrVoid {
+    let mut registry = registry();
+    registry.last_id += 1;
+    let id = registry.last_id;
+    registry.entries.insert(
+        id,
+        Entry {
+            object_type: T::object_type(),
+            object: Arc::new(Mutex::new(Box::new(object))),
+        },
+    );
+    id as PtrVoid
+}
+
+fn invalid_handle(message: String) -> Response {
+    Response::error(Error::new(ErrorKind::InvalidHandle, message))
+}
+
+fn lookup(handle: PtrVoid, expected: &str) -> Result<SharedObject, Response> {
+    let registry = registry();
+    match registry.entries.get(&(handle as usize)) {
+        Some(entry) if entry.object_type == expected => Ok(entry.object.clone()),
+        Some(entry) => Err(invalid_handle(format!(
+            "expected {}, got {}",
+            expected, entry.object_type
+        ))),
+        None => Err(invalid_handle(format!("invalid {} handle", expected))),
+    }
+}
+
+pub fn map_object<T, R, F>(handle: PtrVoid, f: F) -> Result<R, Response>
+where
+    T: RegistryObject,
+    F: FnOnce(&mut T) -> R,
+{
+    let object = lookup(handle, T::object_type())?;
+    let mut object = object.lock().unwrap_or_else(|e| e.into_inner());
+    match object.downcast_mut::<T>() {
+        Some(v) => Ok(f(v)),
+        None => Err(invalid_handle(format!("invalid {} handle", T::object_type()))),
+    }
+}
+
+pub fn with_object<T, F>(handle: PtrVoid, f: F) -> Response
+where
+    T: RegistryObject,
+    F: FnOnce(&mut T) -> Response,
+{
+    match map_object(handle, f) {
+        Ok(v) | Err(v) => v,
+    }
+}
+
+pub fn take<T: RegistryObject>(handle: PtrVoid) -> Result<T, Response> {
+    lookup(handle, T::object_type())?;
+    let entry = match registry().entries.remove(&(handle as usize)) {
+        Some(v) => v,
+        None => return Err(invalid_handle(format!("invalid {} handle", T::object_type()))),
+    };
+    let object = match Arc::try_unwrap(entry.object) {
+        Ok(v) => v.into_inner().unwrap_or_else(|e| e.into_inner()),
+        Err(_) => return Err(invalid_handle(format!("{} is still in use", T::object_type()))),
+    };
+    object
+        .downcast::<T>()
+        .map(|v| *v)
+        .map_err(|_| invalid_handle(format!("invalid {} handle", T::object_type())))
+}
+
+pub fn free<T: RegistryObject>(handle: PtrVoid) -> Response {
+    if let Err(e) = lookup(handle, T::object_type()) {
+        return e;
+    }
+    registry().entries.remove(&(handle as usize));
+    Response::success_null()
+}
+
+pub fn free_any(handle: PtrVoid) -> bool {
+    registry().entries.remove(&(handle as usize)).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Foo(u32);
+    struct Bar;
+
+    impl RegistryObject for Foo {
+        fn object_type() -> &'static str {
+            "foo"
+        }
+    }
+
+    impl RegistryObject for Bar {
+        fn object_type() -> &'static str {
+            "bar"
+        }
+    }
+
+    #[test]
+    fn test_type_checked_access() {
+        let foo = register(Foo(42));
+        let response = with_object(foo, |foo: &mut Foo| Response::success_uint32(foo.0));
+        assert_eq!(response.status_code, crate::response::SUCCESS);
+
+        let response = with_object(foo, |_: &mut Bar| Response::success_null());
+        assert_eq!(response.status_code, ErrorKind::InvalidHandle as u32);
+
+        assert_eq!(free::<Bar>(foo).status_code, ErrorKind::InvalidHandle as u32);
+        assert_eq!(take::<Foo>(foo).ok().map(|v| v.0), Some(42));
+        assert_eq!(free::<Foo>(foo).status_code, ErrorKind::InvalidHandle as u32);
+        assert!(!free_any(foo));
+    }
+}
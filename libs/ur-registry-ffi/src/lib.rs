@@ -1,13 +1,22 @@
 mod crypto_hd_key;
+mod error;
+mod handle;
 mod response;
 mod solana;
 mod extend;
 mod types;
 mod ur_decoder;
+mod ur_decoder_session;
+mod ur_part;
+mod qr;
+mod animation;
 mod utils;
 mod ur_encoder;
 mod ethereum;
 mod cardano;
 mod crypto_output;
 mod crypto_account;
-mod crypto_psbt;
\ No newline at end of file
+mod crypto_psbt;
+
+#[cfg(test)]
+mod boundary_tests;
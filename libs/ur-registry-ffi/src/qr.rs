@@ -0,0 +1,287 @@
+use crate::error::{Error, ErrorKind};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::{PtrBytes, PtrString};
+use crate::utils::{convert_ptr_string_to_string, parse_ptr_bytes};
+use qrcode::{Color, EcLevel, QrCode};
+use serde_json::json;
+
+pub const MAX_QUIET_ZONE: u32 = 32;
+pub const MAX_MODULE_SIZE: u32 = 64;
+pub const MAX_FRAME_SIZE: u32 = 8192;
+
+/// A rendered QR code including its quiet zone, `modules` is row-major and `true` is dark.
+pub struct QrMatrix {
+    pub width: usize,
+    pub modules: Vec<bool>,
+}
+
+fn ec_level(ec_level: u32) -> Result<EcLevel, Error> {
+    match ec_level {
+        0 => Ok(EcLevel::L),
+        1 => Ok(EcLevel::M),
+        2 => Ok(EcLevel::Q),
+        3 => Ok(EcLevel::H),
+        v => Err(Error::invalid_argument(
+            "ec_level",
+            format!("error correction level {} is invalid, expected 0 (L) to 3 (H)", v),
+        )),
+    }
+}
+
+/// URs are case-insensitive, uppercasing them lets the QR code use the denser alphanumeric mode.
+fn qr_text(text: &str) -> String {
+    if text.get(..3).is_some_and(|prefix| prefix.eq_ignore_ascii_case("ur:")) {
+        text.to_uppercase()
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn encode_matrix(text: &str, ec_level_value: u32, quiet_zone: u32) -> Result<QrMatrix, Error> {
+    let ec_level = ec_level(ec_level_value)?;
+    if quiet_zone > MAX_QUIET_ZONE {
+        return Err(Error::invalid_argument(
+            "quiet_zone",
+            format!("quiet zone must not exceed {} modules", MAX_QUIET_ZONE),
+        ));
+    }
+    let code = QrCode::with_error_correction_level(qr_text(text).as_bytes(), ec_level)
+        .map_err(|e| Error::invalid_argument("text", e))?;
+    let code_width = code.width();
+    let quiet_zone = quiet_zone as usize;
+    let width = code_width + 2 * quiet_zone;
+    let mut modules = vec![false; width * width];
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        let (x, y) = (i % code_width, i / code_width);
+        modules[(y + quiet_zone) * width + x + quiet_zone] = color == Color::Dark;
+    }
+    Ok(QrMatrix { width, modules })
+}
+
+fn check_module_size(module_size: u32) -> Result<usize, Error> {
+    if !(1..=MAX_MODULE_SIZE).contains(&module_size) {
+        return Err(Error::invalid_argument(
+            "module_size",
+            format!("module size must be between 1 and {} pixels", MAX_MODULE_SIZE),
+        ));
+    }
+    Ok(module_size as usize)
+}
+
+impl QrMatrix {
+    /// The rendered width in pixels, which is held to the same bound as camera frames.
+    pub fn render_size(&self, module_size: usize) -> Result<usize, Error> {
+        let size = self.width * module_size;
+        if size > MAX_FRAME_SIZE as usize {
+            return Err(Error::new(
+                ErrorKind::LimitExceeded,
+                format!("QR code would be {} pixels wide, at most {} are allowed", size, MAX_FRAME_SIZE),
+            ));
+        }
+        Ok(size)
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.width + x]
+    }
+
+    pub fn to_svg(&self, module_size: usize) -> Result<String, Error> {
+        let size = self.render_size(module_size)?;
+        let mut path = String::new();
+        for y in 0..self.width {
+            for x in 0..self.width {
+                if self.is_dark(x, y) {
+                    path.push_str(&format!("M{},{}h1v1h-1z", x, y));
+                }
+            }
+        }
+        Ok(format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {width} {width}" shape-rendering="crispEdges">"#,
+                r##"<rect width="{width}" height="{width}" fill="#ffffff"/><path fill="#000000" d="{path}"/></svg>"##
+            ),
+            size = size,
+            width = self.width,
+            path = path
+        ))
+    }
+
+    /// One 8-bit grayscale sample per pixel, row-major.
+    pub fn to_luma(&self, module_size: usize) -> Result<(usize, Vec<u8>), Error> {
+        let size = self.render_size(module_size)?;
+        let mut pixels = Vec::with_capacity(size * size);
+        for y in 0..size {
+            for x in 0..size {
+                pixels.push(if self.is_dark(x / module_size, y / module_size) { 0 } else { 255 });
+            }
+        }
+        Ok((size, pixels))
+    }
+
+    pub fn to_png(&self, module_size: usize) -> Result<Vec<u8>, Error> {
+        let (size, pixels) = self.to_luma(module_size)?;
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, size as u32, size as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|e| Error::new(ErrorKind::Internal, e))?;
+        Ok(png)
+    }
+}
+
+/// Copies a camera frame out of host memory, rows may be padded to `stride` bytes.
+pub fn parse_luma_frame(
+    width: u32,
+    height: u32,
+    stride: u32,
+    luma: PtrBytes,
+    luma_len: usize,
+) -> Result<Vec<u8>, Error> {
+    if !(1..=MAX_FRAME_SIZE).contains(&width) || !(1..=MAX_FRAME_SIZE).contains(&height) {
+        return Err(Error::invalid_argument(
+            "width",
+            format!("frame dimensions must be between 1 and {} pixels", MAX_FRAME_SIZE),
+        ));
+    }
+    if stride < width {
+        return Err(Error::invalid_argument("stride", "stride must not be less than width"));
+    }
+    let required = stride as usize * (height as usize - 1) + width as usize;
+    if luma_len < required {
+        return Err(Error::invalid_argument(
+            "luma",
+            format!("frame needs {} bytes, got {}", required, luma_len),
+        ));
+    }
+    parse_ptr_bytes(luma, required).map_err(|e| e.for_field("luma"))
+}
+
+/// Finds every QR code in an 8-bit grayscale frame and returns the text of those that decode.
+pub fn decode_luma(width: usize, height: usize, stride: usize, luma: &[u8]) -> Vec<String> {
+    let mut image = rqrr::PreparedImage::prepare_from_greyscale(width, height, |x, y| luma[y * stride + x]);
+    image
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| grid.decode().ok().map(|(_, content)| content))
+        .collect()
+}
+
+/// Returns `{"width": 29, "modules": "0101..."}` where `modules` is the hex of one byte per module
+/// (`01` dark, `00` light) for a row-major square of `width * width` modules.
+#[no_mangle]
+pub extern "C" fn qr_encode_matrix(text: PtrString, ec_level: u32, quiet_zone: u32) -> PtrResponse {
+    catch_panic(|| {
+        let text = match convert_ptr_string_to_string(text) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("text")).c_ptr(),
+        };
+        match encode_matrix(&text, ec_level, quiet_zone) {
+            Ok(matrix) => Response::success_string(
+                json!({
+                    "width": matrix.width,
+                    "modules": hex::encode(matrix.modules.into_iter().map(u8::from).collect::<Vec<u8>>()),
+                })
+                .to_string(),
+            ),
+            Err(e) => Response::error(e),
+        }
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn qr_encode_svg(text: PtrString, ec_level: u32, quiet_zone: u32, module_size: u32) -> PtrResponse {
+    catch_panic(|| {
+        let text = match convert_ptr_string_to_string(text) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("text")).c_ptr(),
+        };
+        let svg = check_module_size(module_size)
+            .and_then(|module_size| encode_matrix(&text, ec_level, quiet_zone)?.to_svg(module_size));
+        match svg {
+            Ok(svg) => Response::success_string(svg),
+            Err(e) => Response::error(e),
+        }
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn qr_encode_png(text: PtrString, ec_level: u32, quiet_zone: u32, module_size: u32) -> PtrResponse {
+    catch_panic(|| {
+        let text = match convert_ptr_string_to_string(text) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e.for_field("text")).c_ptr(),
+        };
+        let png = check_module_size(module_size)
+            .and_then(|module_size| encode_matrix(&text, ec_level, quiet_zone)?.to_png(module_size));
+        match png {
+            Ok(png) => Response::success_bytes(png),
+            Err(e) => Response::error(e),
+        }
+        .c_ptr()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_matrix() {
+        let matrix = encode_matrix("ur:bytes/hdcxdwinvezm", 0, 4).unwrap();
+        assert_eq!(matrix.width, 21 + 8);
+        assert_eq!(matrix.modules.len(), matrix.width * matrix.width);
+        // the top left finder pattern starts right after the quiet zone
+        assert!(!matrix.is_dark(3, 3));
+        assert!(matrix.is_dark(4, 4));
+        assert!(matrix.is_dark(10, 4));
+        assert!(!matrix.is_dark(5, 5));
+
+        let kind = |ec_level, quiet_zone| encode_matrix("ur:bytes/hdcx", ec_level, quiet_zone).err().map(|e| e.kind());
+        assert_eq!(kind(4, 0), Some(ErrorKind::InvalidArgument));
+        assert_eq!(kind(0, MAX_QUIET_ZONE + 1), Some(ErrorKind::InvalidArgument));
+        assert!(encode_matrix(&"ur:bytes/".repeat(1000), 3, 0).is_err());
+    }
+
+    #[test]
+    fn test_alphanumeric_mode() {
+        // 25 alphanumeric characters fit in version 1 at level L, 25 bytes would need version 2
+        let matrix = encode_matrix("ur:bytes/hdcxdwinvezmhdcx", 0, 0).unwrap();
+        assert_eq!(matrix.width, 21);
+
+        // other text is encoded as is, even when its third byte is inside a character
+        assert_eq!(qr_text("aaé"), "aaé");
+        assert_eq!(qr_text("Ur:bytes/hdcx"), "UR:BYTES/HDCX");
+        assert!(encode_matrix("aaé", 0, 0).is_ok());
+    }
+
+    #[test]
+    fn test_render() {
+        let matrix = encode_matrix("ur:bytes/hdcx", 1, 2).unwrap();
+        let svg = matrix.to_svg(4).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(&format!("viewBox=\"0 0 {0} {0}\"", matrix.width)));
+
+        let png = matrix.to_png(4).unwrap();
+        let decoder = png::Decoder::new(png.as_slice());
+        let reader = decoder.read_info().unwrap();
+        assert_eq!(reader.info().width as usize, matrix.width * 4);
+        assert!(check_module_size(0).is_err());
+    }
+
+    #[test]
+    fn test_render_size_limit() {
+        let matrix = encode_matrix("ur:bytes/hdcx", 0, MAX_QUIET_ZONE).unwrap();
+        assert_eq!(matrix.render_size(MAX_MODULE_SIZE as usize).unwrap(), matrix.width * 64);
+
+        let matrix = encode_matrix(&"ur:bytes/".repeat(60), 0, MAX_QUIET_ZONE).unwrap();
+        assert!(matrix.width * MAX_MODULE_SIZE as usize > MAX_FRAME_SIZE as usize);
+        let kind = |e: Error| e.kind();
+        assert_eq!(matrix.to_png(MAX_MODULE_SIZE as usize).err().map(kind), Some(ErrorKind::LimitExceeded));
+        assert_eq!(matrix.to_svg(MAX_MODULE_SIZE as usize).err().map(kind), Some(ErrorKind::LimitExceeded));
+    }
+}
@@ -1,9 +1,23 @@
+use crate::error::{Error, ErrorKind};
+use crate::handle::{register, RegistryObject};
 use crate::types::{PtrString, PtrVoid};
-use crate::utils::str_to_ptr_c_char;
-use std::any::Any;
-use std::ffi::CString;
+use crate::utils::{str_to_ptr_c_char, try_str_to_ptr_c_char};
+use std::ffi::{CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr::null_mut;
 
+/// Every exported function returns a heap allocated `Response`. The host owns it and must
+/// release it with `response_free` once it has read the value.
+///
+/// * `success_string`: the string in `value` belongs to the response and is released with it,
+///   copy it before calling `response_free`.
+/// * `success_bytes`: likewise the `Bytes` buffer in `value` is released with the response, copy
+///   it before calling `response_free`.
+/// * `success_object`: `value` holds an object handle and `value_type` names its registry type
+///   (e.g. `eth-signature`). The object outlives the response, release it with its `*_free`
+///   function or `utils_free`.
+/// * `error`: `status_code` carries the `ErrorKind` and `error_message` its JSON description,
+///   which belongs to the response and is released with it.
 #[repr(C)]
 pub struct Response {
     pub status_code: u32,
@@ -18,12 +32,84 @@ impl Response {
     }
 }
 
+impl Drop for Response {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.value_type.is_null() {
+                match CStr::from_ptr(self.value_type).to_bytes() {
+                    b"STRING" => free_c_string(self.value._string),
+                    b"BYTES" => free_bytes(self.value._bytes),
+                    _ => {}
+                }
+                free_c_string(self.value_type);
+            }
+            free_c_string(self.error_message);
+        }
+    }
+}
+
+unsafe fn free_c_string(s: PtrString) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Runs the body of an exported function, turning a panic into an error response so that it
+/// never unwinds into the host.
+pub fn catch_panic<F: FnOnce() -> PtrResponse>(f: F) -> PtrResponse {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|e| {
+        let message = e
+            .downcast_ref::<&str>()
+            .map(|v| v.to_string())
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Response::error(Error::new(ErrorKind::Internal, message)).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn response_free(response: PtrResponse) {
+    if response.is_null() {
+        return;
+    }
+    drop(Response::from_ptr(response));
+}
+
+/// A byte buffer allocated on the Rust side, `data` is null when `len` is `0`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Bytes {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl Bytes {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        if bytes.is_empty() {
+            return Bytes {
+                data: null_mut(),
+                len: 0,
+            };
+        }
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        Bytes { data, len }
+    }
+}
+
+unsafe fn free_bytes(bytes: Bytes) {
+    if !bytes.data.is_null() && bytes.len != 0 {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(bytes.data, bytes.len)));
+    }
+}
+
 #[repr(C)]
 pub union Value {
     _object: PtrVoid,
     _boolean: bool,
     _uint32: u32,
     _string: PtrString,
+    _bytes: Bytes,
     _null: PtrVoid,
 }
 
@@ -37,9 +123,14 @@ impl Value {
     pub fn uint32(u: u32) -> Self {
         Value { _uint32: u }
     }
-    pub fn string(s: String) -> Self {
+    pub fn string(s: String) -> Result<Self, Error> {
+        Ok(Value {
+            _string: try_str_to_ptr_c_char(s)?,
+        })
+    }
+    pub fn bytes(b: Vec<u8>) -> Self {
         Value {
-            _string: str_to_ptr_c_char(s),
+            _bytes: Bytes::new(b),
         }
     }
     pub fn null() -> Self {
@@ -49,6 +140,22 @@ impl Value {
     pub fn get_object(&self) -> PtrVoid {
         unsafe { self._object }
     }
+
+    pub fn get_uint32(&self) -> u32 {
+        unsafe { self._uint32 }
+    }
+
+    pub fn get_boolean(&self) -> bool {
+        unsafe { self._boolean }
+    }
+
+    pub fn get_string(&self) -> PtrString {
+        unsafe { self._string }
+    }
+
+    pub fn get_bytes(&self) -> Bytes {
+        unsafe { self._bytes }
+    }
 }
 
 pub type PtrResponse = *mut Response;
@@ -67,12 +174,12 @@ impl Response {
         }
     }
 
-    pub fn success_object(o: PtrVoid) -> Self {
+    pub fn success_object<T: RegistryObject>(o: T) -> Self {
         Response {
             status_code: SUCCESS,
             error_message: null_mut(),
-            value: Value::object(o),
-            value_type: str_to_ptr_c_char("OBJECT".to_string()),
+            value: Value::object(register(o)),
+            value_type: str_to_ptr_c_char(T::object_type().to_string()),
         }
     }
 
@@ -94,19 +201,32 @@ impl Response {
         }
     }
 
+    /// Strings holding a NUL byte cannot be handed over as C strings and yield an error instead.
     pub fn success_string(s: String) -> Self {
+        match Value::string(s) {
+            Ok(value) => Response {
+                status_code: SUCCESS,
+                error_message: null_mut(),
+                value,
+                value_type: str_to_ptr_c_char("STRING".to_string()),
+            },
+            Err(e) => Response::error(e),
+        }
+    }
+
+    pub fn success_bytes(b: Vec<u8>) -> Self {
         Response {
             status_code: SUCCESS,
             error_message: null_mut(),
-            value: Value::string(s),
-            value_type: str_to_ptr_c_char("STRING".to_string()),
+            value: Value::bytes(b),
+            value_type: str_to_ptr_c_char("BYTES".to_string()),
         }
     }
 
-    pub fn error(error_message: String) -> Self {
+    pub fn error(error: Error) -> Self {
         Response {
-            status_code: ERROR,
-            error_message: CString::new(error_message).unwrap().into_raw(),
+            status_code: error.kind() as u32,
+            error_message: str_to_ptr_c_char(error.to_json()),
             value: Value::null(),
             value_type: str_to_ptr_c_char("NULL".to_string()),
         }
@@ -114,4 +234,3 @@ impl Response {
 }
 
 pub const SUCCESS: u32 = 0;
-pub const ERROR: u32 = 1;
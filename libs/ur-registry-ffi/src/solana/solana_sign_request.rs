@@ -1,21 +1,39 @@
-use crate::response::{PtrResponse, Response};
-use crate::types::{PtrString, PtrVoid};
-use crate::utils::{convert_ptr_string_to_string, parse_ptr_string_to_bytes};
+use crate::error::{Error, ErrorKind};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::{PtrBytes, PtrString, PtrVoid};
+use crate::ur_encoder::{new_encoder, EncoderOptions, DEFAULT_MAX_FRAGMENT_LEN};
+use crate::utils::{convert_ptr_string_to_string, parse_ptr_bytes, parse_ptr_string_to_bytes};
 
 use ur_registry::crypto_key_path::CryptoKeyPath;
 use ur_registry::solana::sol_sign_request::{SignType, SolSignRequest};
 use ur_registry::traits::{To, RegistryItem};
 
+impl RegistryObject for SolSignRequest {
+    fn object_type() -> &'static str {
+        "sol-sign-request"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match SolSignRequest::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
 }
 
 #[no_mangle]
 pub extern "C" fn solana_sign_request_new() -> PtrResponse {
-    Response::success_object(Box::into_raw(Box::new(SolSignRequest::default())) as PtrVoid).c_ptr()
+    catch_panic(|| {
+        Response::success_object(SolSignRequest::default()).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn solana_sign_request_free(sol_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<SolSignRequest>(sol_sign_request).c_ptr()
+    })
 }
 
 #[no_mangle]
@@ -28,34 +46,68 @@ pub extern "C" fn solana_sign_request_construct(
     origin: PtrString,
     sign_type: u32,
 ) -> PtrResponse {
-    let request_id = match parse_ptr_string_to_bytes(request_id).map_err(|e| Response::error(e)) {
-        Ok(v) => v,
-        Err(e) => return e.c_ptr(),
-    };
-    let sign_data = match parse_ptr_string_to_bytes(sign_data).map_err(|e| Response::error(e)) {
+    catch_panic(|| {
+        let sign_data = match parse_ptr_string_to_bytes(sign_data).map_err(|e| Response::error(e.for_field("sign_data"))) {
+            Ok(v) => v,
+            Err(e) => return e.c_ptr(),
+        };
+        construct(request_id, sign_data, path, xfp, address, origin, sign_type).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn solana_sign_request_construct_from_bytes(
+    request_id: PtrString,
+    sign_data: PtrBytes,
+    sign_data_len: usize,
+    path: PtrString,
+    xfp: u32,
+    address: PtrString,
+    origin: PtrString,
+    sign_type: u32,
+) -> PtrResponse {
+    catch_panic(|| {
+        let sign_data = match parse_ptr_bytes(sign_data, sign_data_len).map_err(|e| Response::error(e.for_field("sign_data"))) {
+            Ok(v) => v,
+            Err(e) => return e.c_ptr(),
+        };
+        construct(request_id, sign_data, path, xfp, address, origin, sign_type).c_ptr()
+    })
+}
+
+fn construct(
+    request_id: PtrString,
+    sign_data: Vec<u8>,
+    path: PtrString,
+    xfp: u32,
+    address: PtrString,
+    origin: PtrString,
+    sign_type: u32,
+) -> Response {
+    let request_id = match parse_ptr_string_to_bytes(request_id).map_err(|e| Response::error(e.for_field("request_id"))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
-    let address = match parse_ptr_string_to_bytes(address).map_err(|e| Response::error(e)) {
+    let address = match parse_ptr_string_to_bytes(address).map_err(|e| Response::error(e.for_field("address"))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
-    let origin = match convert_ptr_string_to_string(origin).map_err(|e| Response::error(e)) {
+    let origin = match convert_ptr_string_to_string(origin).map_err(|e| Response::error(e.for_field("origin"))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
-    let sign_type = match SignType::from_u32(sign_type).map_err(|e| Response::error(e)) {
+    let sign_type = match SignType::from_u32(sign_type).map_err(|e| Response::error(Error::invalid_argument("sign_type", e))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
-    let path = match convert_ptr_string_to_string(path).map_err(|e| Response::error(e)) {
+    let path = match convert_ptr_string_to_string(path).map_err(|e| Response::error(e.for_field("path"))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
     let derivation_path = match CryptoKeyPath::from_path(path, Some(xfp.to_be_bytes()))
-        .map_err(|e| Response::error(e)) {
+        .map_err(|e| Response::error(Error::invalid_path("path", e))) {
         Ok(v) => v,
-        Err(e) => return e.c_ptr(),
+        Err(e) => return e,
     };
     let request = SolSignRequest::new(
         Some(request_id),
@@ -65,24 +117,48 @@ pub extern "C" fn solana_sign_request_construct(
         Some(origin),
         sign_type,
     );
-    Response::success_object(Box::into_raw(Box::new(request)) as PtrVoid).c_ptr()
+    Response::success_object(request)
+}
+
+#[no_mangle]
+pub extern "C" fn solana_sign_request_get_ur_encoder(sol_sign_request: PtrVoid) -> PtrResponse {
+    solana_sign_request_get_ur_encoder_with_options(sol_sign_request, DEFAULT_MAX_FRAGMENT_LEN, 0, 0)
 }
 
 #[no_mangle]
-pub extern "C" fn solana_sign_request_get_ur_encoder(sol_sign_request: &mut SolSignRequest) -> PtrResponse {
-    let message = sol_sign_request.to_bytes().unwrap();
-    let ur_encoder = ur::Encoder::new(
-        message.as_slice(),
-        400,
-        SolSignRequest::get_registry_type().get_type(),
-    )
-    .unwrap();
-    Response::success_object(Box::into_raw(Box::new(ur_encoder)) as PtrVoid).c_ptr()
+pub extern "C" fn solana_sign_request_get_ur_encoder_with_options(
+    sol_sign_request: PtrVoid,
+    max_fragment_len: u32,
+    min_fragment_len: u32,
+    first_seq_num: u32,
+) -> PtrResponse {
+    catch_panic(|| {
+        with_object(sol_sign_request, |sol_sign_request: &mut SolSignRequest| {
+            let message = match sol_sign_request.to_bytes() {
+                Ok(v) => v,
+                Err(e) => return Response::error(Error::new(ErrorKind::CborError, e)),
+            };
+            match new_encoder(
+                message.as_slice(),
+                SolSignRequest::get_registry_type().get_type(),
+                EncoderOptions::new(max_fragment_len, min_fragment_len, first_seq_num),
+            ) {
+                Ok(ur_encoder) => Response::success_object(ur_encoder),
+                Err(e) => Response::error(e),
+            }
+        })
+        .c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn solana_sign_request_get_request_id(sol_sign_request: &mut SolSignRequest) -> PtrResponse {
-    sol_sign_request.get_request_id().map_or(Response::success_null().c_ptr(), |id| {
-        Response::success_string(hex::encode(id)).c_ptr()
+pub extern "C" fn solana_sign_request_get_request_id(sol_sign_request: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(sol_sign_request, |sol_sign_request: &mut SolSignRequest| {
+            sol_sign_request.get_request_id().map_or(Response::success_null(), |id| {
+                Response::success_string(hex::encode(id))
+            })
+        })
+        .c_ptr()
     })
 }
\ No newline at end of file
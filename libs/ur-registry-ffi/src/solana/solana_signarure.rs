@@ -1,24 +1,49 @@
+use crate::error::{Error, ErrorKind};
 use ur_registry::solana::sol_signature::SolSignature;
 use ur_registry::traits::From;
-use crate::response::{PtrResponse, Response};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
 use crate::types::PtrVoid;
 
+impl RegistryObject for SolSignature {
+    fn object_type() -> &'static str {
+        "sol-signature"
+    }
+}
+
 pub fn resolve(data: Vec<u8>) -> PtrResponse {
     match SolSignature::try_from(data) {
-        Ok(result) => Response::success_object(Box::into_raw(Box::new(result)) as PtrVoid).c_ptr(),
-        Err(error) => Response::error(error.to_string()).c_ptr(),
+        Ok(result) => Response::success_object(result).c_ptr(),
+        Err(error) => Response::error(Error::new(ErrorKind::CborError, error)).c_ptr(),
     }
 }
 
 #[no_mangle]
-pub extern "C" fn solana_signature_get_signature(solana_signarure: &mut SolSignature) -> PtrResponse {
-    Response::success_string(hex::encode(solana_signarure.get_signature())).c_ptr()
+pub extern "C" fn solana_signature_free(solana_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<SolSignature>(solana_signature).c_ptr()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn solana_signature_get_request_id(solana_signature: &mut SolSignature) -> PtrResponse {
-    match solana_signature.get_request_id() {
-        Some(v) => Response::success_string(hex::encode(v)).c_ptr(),
-        None => Response::error(format!("No request id supplied")).c_ptr()
-    }
-}
\ No newline at end of file
+pub extern "C" fn solana_signature_get_signature(solana_signarure: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(solana_signarure, |solana_signarure: &mut SolSignature| {
+            Response::success_string(hex::encode(solana_signarure.get_signature()))
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn solana_signature_get_request_id(solana_signature: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(solana_signature, |solana_signature: &mut SolSignature| {
+            match solana_signature.get_request_id() {
+                Some(v) => Response::success_string(hex::encode(v)),
+                None => Response::error(Error::new(ErrorKind::NotFound, "No request id supplied"))
+            }
+        })
+        .c_ptr()
+    })
+}
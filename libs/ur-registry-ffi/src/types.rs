@@ -3,3 +3,4 @@ use std::os::raw::c_char;
 
 pub type PtrVoid = *mut c_void;
 pub type PtrString = *mut c_char;
+pub type PtrBytes = *const u8;
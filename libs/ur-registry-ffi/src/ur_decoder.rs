@@ -1,70 +1,707 @@
-use crate::response::{PtrResponse, Response, Value};
-use crate::types::PtrString;
+use crate::error::{Error, ErrorKind};
+use crate::handle::{free, map_object, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::qr::{decode_luma, parse_luma_frame};
+use crate::types::{PtrBytes, PtrString, PtrVoid};
+use crate::ur_part::{decode_payload, parse_fountain_part, parse_header};
+use crate::utils::convert_ptr_string_to_string;
 use hex::encode;
-use std::ffi::{c_void, CStr};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeSet;
 use ur::Decoder;
+use ur_registry::cardano::cardano_catalyst_signature::CardanoCatalystSignature;
+use ur_registry::cardano::cardano_sign_cip8_data_signature::CardanoSignCip8DataSignature;
+use ur_registry::cardano::cardano_sign_data_signature::CardanoSignDataSignature;
+use ur_registry::cardano::cardano_signature::CardanoSignature;
+use ur_registry::crypto_account::CryptoAccount;
+use ur_registry::crypto_hd_key::CryptoHDKey;
+use ur_registry::crypto_output::CryptoOutput;
+use ur_registry::crypto_psbt::CryptoPSBT;
+use ur_registry::ethereum::eth_sign_request::EthSignRequest;
+use ur_registry::ethereum::eth_signature::EthSignature;
+use ur_registry::extend::crypto_multi_accounts::CryptoMultiAccounts;
+use ur_registry::solana::sol_sign_request::SolSignRequest;
+use ur_registry::solana::sol_signature::SolSignature;
+use ur_registry::traits::RegistryItem;
+
+/// Identifies the message a part belongs to, so that parts of another message are rejected
+/// before they reach `ur::Decoder`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Message {
+    SinglePart {
+        ur_type: String,
+        message: Vec<u8>,
+    },
+    MultiPart {
+        ur_type: String,
+        seq_len: u32,
+        message_len: u64,
+        checksum: u32,
+    },
+}
+
+pub const DEFAULT_MAX_MESSAGE_LEN: u32 = 8 * 1024 * 1024;
+pub const DEFAULT_MAX_FRAGMENT_COUNT: u32 = 32_768;
+pub const DEFAULT_MAX_MIXED_PARTS: u32 = 32_768;
+
+/// Bounds on what a decoder accepts, checked against the part header before anything reaches
+/// `ur::Decoder`, which would otherwise size its buffers after whatever a part claims. Each limit
+/// falls back to its default when `0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecoderLimits {
+    pub max_message_len: u32,
+    pub max_fragment_count: u32,
+    pub max_mixed_parts: u32,
+}
+
+impl DecoderLimits {
+    pub fn new(max_message_len: u32, max_fragment_count: u32, max_mixed_parts: u32) -> Self {
+        let or_default = |value: u32, default: u32| if value == 0 { default } else { value };
+        DecoderLimits {
+            max_message_len: or_default(max_message_len, DEFAULT_MAX_MESSAGE_LEN),
+            max_fragment_count: or_default(max_fragment_count, DEFAULT_MAX_FRAGMENT_COUNT),
+            max_mixed_parts: or_default(max_mixed_parts, DEFAULT_MAX_MIXED_PARTS),
+        }
+    }
+
+    /// Parts are kept as bytewords for `ur_decoder_serialize`, about two bytes per message byte,
+    /// so the replay buffer may hold eight times the message length plus room for headers.
+    pub fn max_stored_bytes(&self) -> u64 {
+        self.max_message_len as u64 * 8 + 64 * 1024
+    }
+}
+
+impl Default for DecoderLimits {
+    fn default() -> Self {
+        DecoderLimits::new(0, 0, 0)
+    }
+}
+
+fn limit_exceeded(message: String) -> Error {
+    Error::new(ErrorKind::LimitExceeded, message)
+}
+
+/// `ur::Decoder` drops the UR header once a part is decoded, so the type and the progress of a
+/// multi-part scan are tracked alongside. Its fragment state is not exposed either, so every
+/// distinct part is kept to be able to save the decoder and replay them later.
+#[derive(Default)]
+pub struct URDecoder {
+    decoder: Decoder,
+    message: Option<Message>,
+    processed_parts_count: u32,
+    received_parts: BTreeSet<u32>,
+    parts: Vec<String>,
+    stored_bytes: u64,
+    limits: DecoderLimits,
+}
+
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    version: u32,
+    processed_parts_count: u32,
+    parts: Vec<String>,
+    #[serde(default)]
+    limits: DecoderLimits,
+}
+
+/// Parses the header and fountain metadata of a lowercase UR part, returning its sequence number
+/// and the message it belongs to.
+pub fn parse_message(ur: &str) -> Result<(u32, Message), Error> {
+    let header = parse_header(ur)?;
+    let payload = decode_payload(header.payload)?;
+    match header.sequence {
+        Some((seq, seq_len)) => {
+            let part = parse_fountain_part(&payload)?;
+            if part.seq != seq || part.seq_len != seq_len {
+                return Err(Error::new(
+                    ErrorKind::InvalidFragment,
+                    format!("sequence {}-{} does not match the part", seq, seq_len),
+                ));
+            }
+            let message = Message::MultiPart {
+                ur_type: header.ur_type.to_string(),
+                seq_len,
+                message_len: part.message_len,
+                checksum: part.checksum,
+            };
+            Ok((seq, message))
+        }
+        None => {
+            let message = Message::SinglePart {
+                ur_type: header.ur_type.to_string(),
+                message: payload,
+            };
+            Ok((1, message))
+        }
+    }
+}
+
+impl URDecoder {
+    pub fn with_limits(limits: DecoderLimits) -> Self {
+        URDecoder {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    pub fn receive(&mut self, ur: &str) -> Result<(), Error> {
+        let (seq, message) = parse_message(ur)?;
+        self.receive_part(ur, seq, message)
+    }
+
+    /// Feeds a part already parsed with `parse_message`.
+    pub fn receive_part(&mut self, ur: &str, seq: u32, message: Message) -> Result<(), Error> {
+        if let Some(current) = &self.message {
+            if current != &message {
+                return Err(Error::new(
+                    ErrorKind::DifferentMessage,
+                    "part belongs to a different message",
+                ));
+            }
+        }
+        self.check_limits(ur, seq, &message)?;
+        self.decoder
+            .receive(ur)
+            .map_err(|e| Error::new(ErrorKind::DecodeFailure, e))?;
+        self.message = Some(message);
+        self.processed_parts_count += 1;
+        if self.received_parts.insert(seq) {
+            self.stored_bytes += ur.len() as u64;
+            self.parts.push(ur.to_string());
+        }
+        Ok(())
+    }
+
+    fn check_limits(&self, ur: &str, seq: u32, message: &Message) -> Result<(), Error> {
+        let limits = &self.limits;
+        let is_new = !self.received_parts.contains(&seq);
+        if is_new && self.stored_bytes + ur.len() as u64 > limits.max_stored_bytes() {
+            return Err(limit_exceeded(format!(
+                "stored parts exceed {} bytes",
+                limits.max_stored_bytes()
+            )));
+        }
+        match message {
+            Message::SinglePart { message, .. } if message.len() as u64 > limits.max_message_len as u64 => Err(
+                limit_exceeded(format!("message length exceeds {} bytes", limits.max_message_len)),
+            ),
+            Message::SinglePart { .. } => Ok(()),
+            Message::MultiPart { seq_len, message_len, .. } => {
+                if *message_len > limits.max_message_len as u64 {
+                    return Err(limit_exceeded(format!(
+                        "message length {} exceeds {} bytes",
+                        message_len, limits.max_message_len
+                    )));
+                }
+                if *seq_len > limits.max_fragment_count {
+                    return Err(limit_exceeded(format!(
+                        "fragment count {} exceeds {}",
+                        seq_len, limits.max_fragment_count
+                    )));
+                }
+                // parts past the fragment count are mixed, every distinct part is stored for the
+                // snapshot, so the cap applies to the stored parts that are not pure fragments
+                if seq > *seq_len && is_new {
+                    let fragments = self.received_parts.range(..=seq_len).count();
+                    if self.parts.len() - fragments >= limits.max_mixed_parts as usize {
+                        return Err(limit_exceeded(format!(
+                            "more than {} mixed parts",
+                            limits.max_mixed_parts
+                        )));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            processed_parts_count: self.processed_parts_count,
+            parts: self.parts.clone(),
+            limits: self.limits,
+        }
+    }
+
+    fn restore(snapshot: Snapshot) -> Result<Self, Error> {
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(Error::invalid_argument(
+                "state",
+                format!("unsupported state version {}", snapshot.version),
+            ));
+        }
+        // the state comes back from the host, so its limits may tighten the defaults but never
+        // raise them
+        let limits = snapshot.limits;
+        let limits = DecoderLimits::new(
+            limits.max_message_len.min(DEFAULT_MAX_MESSAGE_LEN),
+            limits.max_fragment_count.min(DEFAULT_MAX_FRAGMENT_COUNT),
+            limits.max_mixed_parts.min(DEFAULT_MAX_MIXED_PARTS),
+        );
+        let mut decoder = URDecoder::with_limits(limits);
+        for part in &snapshot.parts {
+            decoder.receive(part)?;
+        }
+        decoder.processed_parts_count = snapshot.processed_parts_count.max(decoder.processed_parts_count);
+        Ok(decoder)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.decoder.complete()
+    }
+
+    pub fn received_parts_count(&self) -> u32 {
+        self.received_parts.len() as u32
+    }
+
+    pub fn ur_type(&self) -> Option<&str> {
+        match &self.message {
+            Some(Message::SinglePart { ur_type, .. }) | Some(Message::MultiPart { ur_type, .. }) => {
+                Some(ur_type)
+            }
+            None => None,
+        }
+    }
+
+    pub fn expected_part_count(&self) -> u32 {
+        match &self.message {
+            Some(Message::SinglePart { .. }) => 1,
+            Some(Message::MultiPart { seq_len, .. }) => *seq_len,
+            None => 0,
+        }
+    }
+
+    /// Same estimate as the reference implementation: a fountain decoder typically needs about
+    /// 1.75 times the fragment count of mixed parts, capped at 99% until the message is complete.
+    pub fn estimated_percent_complete(&self) -> u32 {
+        if self.decoder.complete() {
+            return 100;
+        }
+        if self.expected_part_count() == 0 {
+            return 0;
+        }
+        let estimated = self.processed_parts_count as f64 / (self.expected_part_count() as f64 * 1.75);
+        (estimated.min(0.99) * 100.0) as u32
+    }
+}
+
+impl RegistryObject for URDecoder {
+    fn object_type() -> &'static str {
+        "ur-decoder"
+    }
+}
+
+struct Resolver {
+    object_type: &'static str,
+    ur_type: String,
+    resolve: fn(Vec<u8>) -> PtrResponse,
+}
+
+fn resolver<T: RegistryObject + RegistryItem>(resolve: fn(Vec<u8>) -> PtrResponse) -> Resolver {
+    Resolver {
+        object_type: T::object_type(),
+        ur_type: T::get_registry_type().get_type(),
+        resolve,
+    }
+}
+
+fn resolvers() -> Vec<Resolver> {
+    vec![
+        resolver::<CryptoMultiAccounts>(crate::extend::crypto_multi_accounts::resolve),
+        resolver::<CryptoHDKey>(crate::crypto_hd_key::resolve),
+        resolver::<CryptoAccount>(crate::crypto_account::resolve),
+        resolver::<CryptoOutput>(crate::crypto_output::resolve),
+        resolver::<CryptoPSBT>(crate::crypto_psbt::resolve),
+        resolver::<SolSignature>(crate::solana::solana_signarure::resolve),
+        resolver::<SolSignRequest>(crate::solana::solana_sign_request::resolve),
+        resolver::<EthSignature>(crate::ethereum::eth_signarure::resolve),
+        resolver::<EthSignRequest>(crate::ethereum::eth_sign_request::resolve),
+        resolver::<CardanoSignature>(crate::cardano::cardano_signature::resolve),
+        resolver::<CardanoCatalystSignature>(crate::cardano::cardano_catalyst_signature::resolve),
+        resolver::<CardanoSignCip8DataSignature>(crate::cardano::cardano_sign_cip8_data_signature::resolve),
+        resolver::<CardanoSignDataSignature>(crate::cardano::cardano_sign_data_signature::resolve),
+    ]
+}
+
+/// Decodes a single-part UR without a decoder, returning `{"type": ..., "cbor": <hex>}`.
+#[no_mangle]
+pub extern "C" fn ur_decode_single(ur: PtrString) -> PtrResponse {
+    catch_panic(|| {
+        let ur_str = match convert_ptr_string_to_string(ur) {
+            Ok(value) => value.to_lowercase(),
+            Err(error) => return Response::error(error.for_field("ur")).c_ptr(),
+        };
+        let header = match parse_header(&ur_str) {
+            Ok(v) => v,
+            Err(error) => return Response::error(error.for_field("ur")).c_ptr(),
+        };
+        if let Some((seq, seq_len)) = header.sequence {
+            return Response::error(
+                Error::new(
+                    ErrorKind::NotSinglePart,
+                    format!("part {} of {} of a multi-part UR, use a decoder instead", seq, seq_len),
+                )
+                .for_field("ur"),
+            )
+            .c_ptr();
+        }
+        match decode_payload(header.payload) {
+            Ok(cbor) => Response::success_string(json!({"type": header.ur_type, "cbor": encode(cbor)}).to_string()),
+            Err(error) => Response::error(error.for_field("ur")),
+        }
+        .c_ptr()
+    })
+}
 
 #[no_mangle]
 pub extern "C" fn ur_decoder_new() -> PtrResponse {
-    Response::success_object(Box::into_raw(Box::new(ur::Decoder::default())) as *mut c_void).c_ptr()
+    catch_panic(|| {
+        Response::success_object(URDecoder::default()).c_ptr()
+    })
 }
 
+/// Creates a decoder that rejects parts of messages longer than `max_message_len` bytes or split
+/// into more than `max_fragment_count` fragments, and stops accepting new mixed parts once
+/// `max_mixed_parts` are held. The parts kept for `ur_decoder_serialize` are bounded to eight
+/// times `max_message_len` bytes. `0` selects the default for that limit.
 #[no_mangle]
-pub extern "C" fn ur_decoder_receive(decoder: &mut Decoder, ur: PtrString) -> PtrResponse {
-    let ur_str = match unsafe { CStr::from_ptr(ur) }.to_str() {
-        Ok(value) => value.to_lowercase(),
-        Err(error) => return Response::error(error.to_string()).c_ptr(),
-    };
-    match decoder.receive(ur_str.as_str()) {
-        Err(error) => Response::error(format!("No data received before get result")).c_ptr(),
-        _ => Response::success_null().c_ptr(),
-    }
+pub extern "C" fn ur_decoder_new_with_limits(
+    max_message_len: u32,
+    max_fragment_count: u32,
+    max_mixed_parts: u32,
+) -> PtrResponse {
+    catch_panic(|| {
+        let limits = DecoderLimits::new(max_message_len, max_fragment_count, max_mixed_parts);
+        Response::success_object(URDecoder::with_limits(limits)).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_free(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<URDecoder>(decoder).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_receive(decoder: PtrVoid, ur: PtrString) -> PtrResponse {
+    catch_panic(|| {
+        let ur_str = match convert_ptr_string_to_string(ur) {
+            Ok(value) => value.to_lowercase(),
+            Err(error) => return Response::error(error.for_field("ur")).c_ptr(),
+        };
+        with_object(decoder, |decoder: &mut URDecoder| match decoder.receive(ur_str.as_str()) {
+            Err(error) => Response::error(error.for_field("ur")),
+            _ => Response::success_null(),
+        })
+        .c_ptr()
+    })
+}
+
+/// Scans a grayscale camera frame for QR codes and feeds each one into the decoder. Returns
+/// `{"found": 2, "received": 1, "rejected": [<error>], "complete": false}` where `rejected` holds
+/// one error object per code that was not accepted, e.g. a QR code that is not a UR.
+#[no_mangle]
+pub extern "C" fn ur_decoder_receive_frame(
+    decoder: PtrVoid,
+    width: u32,
+    height: u32,
+    stride: u32,
+    luma: PtrBytes,
+    luma_len: usize,
+) -> PtrResponse {
+    catch_panic(|| {
+        let luma = match parse_luma_frame(width, height, stride, luma, luma_len) {
+            Ok(v) => v,
+            Err(error) => return Response::error(error).c_ptr(),
+        };
+        with_object(decoder, |decoder: &mut URDecoder| {
+            let codes = decode_luma(width as usize, height as usize, stride as usize, &luma);
+            let mut received = 0;
+            let mut rejected = vec![];
+            for code in &codes {
+                match decoder.receive(&code.to_lowercase()) {
+                    Ok(_) => received += 1,
+                    Err(error) => rejected.push(error.to_value()),
+                }
+            }
+            Response::success_string(
+                json!({
+                    "found": codes.len(),
+                    "received": received,
+                    "rejected": rejected,
+                    "complete": decoder.is_complete(),
+                })
+                .to_string(),
+            )
+        })
+        .c_ptr()
+    })
 }
 
+/// Drops every part received so far, so the decoder can be reused for another message. The
+/// limits it was created with are kept.
 #[no_mangle]
-pub extern "C" fn ur_decoder_is_complete(decoder: &mut Decoder) -> PtrResponse {
-    Response::success_boolean(decoder.complete()).c_ptr()
+pub extern "C" fn ur_decoder_reset(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| {
+            *decoder = URDecoder::with_limits(decoder.limits);
+            Response::success_null()
+        })
+        .c_ptr()
+    })
 }
 
-fn get_result(decoder: &mut Decoder) -> Result<Vec<u8>, String> {
-    match decoder.message() {
+/// Saves the progress of the decoder as a JSON string, so a scan interrupted by the host being
+/// killed can be resumed with `ur_decoder_deserialize`.
+#[no_mangle]
+pub extern "C" fn ur_decoder_serialize(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| match serde_json::to_string(&decoder.snapshot()) {
+            Ok(state) => Response::success_string(state),
+            Err(error) => Response::error(Error::new(ErrorKind::Internal, error)),
+        })
+        .c_ptr()
+    })
+}
+
+/// Restores a decoder saved with `ur_decoder_serialize`. Its limits are rebuilt from the state
+/// and capped at the defaults.
+#[no_mangle]
+pub extern "C" fn ur_decoder_deserialize(state: PtrString) -> PtrResponse {
+    catch_panic(|| {
+        let state = match convert_ptr_string_to_string(state) {
+            Ok(value) => value,
+            Err(error) => return Response::error(error.for_field("state")).c_ptr(),
+        };
+        let snapshot = match serde_json::from_str::<Snapshot>(&state) {
+            Ok(v) => v,
+            Err(_) => return Response::error(Error::invalid_argument("state", "state is invalid")).c_ptr(),
+        };
+        match URDecoder::restore(snapshot) {
+            Ok(decoder) => Response::success_object(decoder),
+            Err(error) => Response::error(error.for_field("state")),
+        }
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_is_complete(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| {
+            Response::success_boolean(decoder.is_complete())
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_expected_part_count(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| {
+            Response::success_uint32(decoder.expected_part_count())
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_processed_parts_count(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| {
+            Response::success_uint32(decoder.processed_parts_count)
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_received_parts_count(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| {
+            Response::success_uint32(decoder.received_parts_count())
+        })
+        .c_ptr()
+    })
+}
+
+/// Estimated progress of the scan in percent, `100` once the message is complete.
+#[no_mangle]
+pub extern "C" fn ur_decoder_estimated_percent_complete(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| {
+            Response::success_uint32(decoder.estimated_percent_complete())
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_get_type(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| match decoder.ur_type() {
+            Some(ur_type) => Response::success_string(ur_type.to_string()),
+            None => Response::error(Error::new(ErrorKind::NotFound, "No part received yet")),
+        })
+        .c_ptr()
+    })
+}
+
+pub fn get_result(decoder: &mut URDecoder) -> Result<Vec<u8>, Error> {
+    match decoder.decoder.message() {
         Ok(m) => match m {
             Some(message) => Ok(message),
-            None => Err(format!("No data received before get result")),
+            None => Err(Error::new(ErrorKind::NotComplete, "No data received before get result")),
         },
-        Err(error) => Err(error.to_string()),
+        Err(error) => Err(Error::new(ErrorKind::DecodeFailure, error)),
     }
 }
 
-#[no_mangle]
-pub extern "C" fn ur_decoder_result(decoder: &mut Decoder) -> PtrResponse {
-    match get_result(decoder) {
-        Ok(message) => Response::success_string(encode(message)).c_ptr(),
-        Err(error) => Response::error(error).c_ptr(),
+fn get_typed_result(decoder: &mut URDecoder) -> Result<(String, Vec<u8>), Error> {
+    let result = get_result(decoder)?;
+    match decoder.ur_type() {
+        Some(ur_type) => Ok((ur_type.to_string(), result)),
+        None => Err(Error::new(ErrorKind::NotFound, "No part received yet")),
     }
 }
 
 #[no_mangle]
-pub extern "C" fn ur_decoder_resolve(decoder: &mut Decoder, target_type: PtrString) -> PtrResponse {
-    let result = match get_result(decoder) {
-        Ok(res) => res,
-        Err(error) => return Response::error(error.to_string()).c_ptr(),
-    };
-    let target = unsafe { CStr::from_ptr(target_type) }.to_str().unwrap();
-    match target {
-        "crypto-multi-accounts" => crate::extend::crypto_multi_accounts::resolve(result),
-        "crypto-hdkey" => crate::crypto_hd_key::resolve(result),
-        "crypto-account" => crate::crypto_account::resolve(result),
-        "crypto-output" => crate::crypto_output::resolve(result),
-        "crypto-psbt" => crate::crypto_psbt::resolve(result),
-        "sol-signature" => crate::solana::solana_signarure::resolve(result),
-        "sol-sign-request" => crate::solana::solana_sign_request::resolve(result),
-        "eth-signature" => crate::ethereum::eth_signarure::resolve(result),
-        "eth-sign-request" => crate::ethereum::eth_sign_request::resolve(result),
-        "cardano-signature" => crate::cardano::cardano_signature::resolve(result),
-        "cardano-catalyst-signature" => crate::cardano::cardano_catalyst_signature::resolve(result),
-        "cardano-sign-cip8-data-signature" => crate::cardano::cardano_sign_cip8_data_signature::resolve(result),
-        "cardano-sign-data-signature" => crate::cardano::cardano_sign_data_signature::resolve(result),
-        t => Response::error(format!("type {} is not supported yet", t)).c_ptr(),
+pub extern "C" fn ur_decoder_result(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| match get_result(decoder) {
+            Ok(message) => Response::success_string(encode(message)),
+            Err(error) => Response::error(error),
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_result_bytes(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(decoder, |decoder: &mut URDecoder| match get_result(decoder) {
+            Ok(message) => Response::success_bytes(message),
+            Err(error) => Response::error(error),
+        })
+        .c_ptr()
+    })
+}
+
+/// `target_type` is either the registry type of the result object or the UR type it is encoded
+/// with, which only differ for `cardano-catalyst-signature`.
+#[no_mangle]
+pub extern "C" fn ur_decoder_resolve(decoder: PtrVoid, target_type: PtrString) -> PtrResponse {
+    catch_panic(|| {
+        let (ur_type, result) = match map_object(decoder, get_typed_result) {
+            Ok(Ok(res)) => res,
+            Ok(Err(error)) => return Response::error(error).c_ptr(),
+            Err(error) => return error.c_ptr(),
+        };
+        let target = match convert_ptr_string_to_string(target_type) {
+            Ok(value) => value,
+            Err(error) => return Response::error(error.for_field("target_type")).c_ptr(),
+        };
+        let resolver = match resolvers()
+            .into_iter()
+            .find(|r| r.object_type == target || r.ur_type == target)
+        {
+            Some(v) => v,
+            None => {
+                return Response::error(
+                    Error::new(ErrorKind::UnsupportedType, format!("type {} is not supported yet", target))
+                        .for_field("target_type"),
+                )
+                .c_ptr()
+            }
+        };
+        if resolver.ur_type != ur_type {
+            return Response::error(
+                Error::new(ErrorKind::TypeMismatch, format!("expected {}, got {}", resolver.ur_type, ur_type))
+                    .for_field("target_type"),
+            )
+            .c_ptr();
+        }
+        (resolver.resolve)(result)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_resolve_auto(decoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        let (ur_type, result) = match map_object(decoder, get_typed_result) {
+            Ok(Ok(res)) => res,
+            Ok(Err(error)) => return Response::error(error).c_ptr(),
+            Err(error) => return error.c_ptr(),
+        };
+        match resolvers().into_iter().find(|r| r.ur_type == ur_type) {
+            Some(resolver) => (resolver.resolve)(result),
+            None => Response::error(Error::new(
+                ErrorKind::UnsupportedType,
+                format!("type {} is not supported yet", ur_type),
+            ))
+            .c_ptr(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ur_encoder::{new_encoder, EncoderOptions};
+
+    #[test]
+    fn test_stored_parts_are_bounded() {
+        // a message of 10 fragments sent as mixed parts only
+        let mut encoder = new_encoder(&[0x70; 1000], "bytes".to_string(), EncoderOptions::new(100, 0, 20)).unwrap();
+        let parts: Vec<String> = (0..3).map(|_| encoder.next_part().unwrap()).collect();
+        let kind = |decoder: &mut URDecoder, part: &str| decoder.receive(part).err().map(|e| e.kind());
+
+        let mut decoder = URDecoder::with_limits(DecoderLimits::new(0, 0, 2));
+        assert_eq!(kind(&mut decoder, &parts[0]), None);
+        assert_eq!(kind(&mut decoder, &parts[1]), None);
+        assert_eq!(kind(&mut decoder, &parts[0]), None);
+        assert_eq!(decoder.parts.len(), 2);
+        assert_eq!(kind(&mut decoder, &parts[2]), Some(ErrorKind::LimitExceeded));
+
+        let mut decoder = URDecoder::with_limits(DecoderLimits::new(1000, 0, 0));
+        assert_eq!(decoder.limits.max_stored_bytes(), 8000 + 64 * 1024);
+        decoder.stored_bytes = decoder.limits.max_stored_bytes() - parts[0].len() as u64 + 1;
+        assert_eq!(kind(&mut decoder, &parts[0]), Some(ErrorKind::LimitExceeded));
+        decoder.stored_bytes -= 1;
+        assert_eq!(kind(&mut decoder, &parts[0]), None);
+        assert_eq!(decoder.stored_bytes, decoder.limits.max_stored_bytes());
+    }
+
+    #[test]
+    fn test_restored_limits_are_bounded() {
+        let restore = |limits: DecoderLimits| {
+            let snapshot = Snapshot {
+                version: SNAPSHOT_VERSION,
+                processed_parts_count: 0,
+                parts: vec![],
+                limits,
+            };
+            URDecoder::restore(snapshot).unwrap().limits
+        };
+        let unbounded = DecoderLimits {
+            max_message_len: u32::MAX,
+            max_fragment_count: u32::MAX,
+            max_mixed_parts: u32::MAX,
+        };
+        assert_eq!(restore(unbounded), DecoderLimits::default());
+        let zero = DecoderLimits {
+            max_message_len: 0,
+            max_fragment_count: 0,
+            max_mixed_parts: 0,
+        };
+        assert_eq!(restore(zero), DecoderLimits::default());
+        assert_eq!(restore(DecoderLimits::new(1000, 10, 3)), DecoderLimits::new(1000, 10, 3));
     }
 }
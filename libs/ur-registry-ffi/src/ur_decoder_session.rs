@@ -0,0 +1,349 @@
+use crate::error::{Error, ErrorKind};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::{PtrString, PtrVoid};
+use crate::ur_decoder::{parse_message, DecoderLimits, Message, URDecoder};
+use crate::utils::convert_ptr_string_to_string;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_MAX_PENDING: u32 = 16;
+/// How many completed messages are remembered to ignore their remaining parts.
+const MAX_FINISHED: usize = 16;
+/// How many completed messages wait for the host to take them.
+const MAX_COMPLETED: usize = 16;
+
+struct Pending {
+    decoder: URDecoder,
+    last_seen: Instant,
+}
+
+/// Removes the entry seen least recently.
+fn evict_oldest<V>(map: &mut HashMap<Message, V>, last_seen: impl Fn(&V) -> Instant) {
+    let oldest = map.iter().min_by_key(|(_, v)| last_seen(v)).map(|(k, _)| k.clone());
+    if let Some(message) = oldest {
+        map.remove(&message);
+    }
+}
+
+/// Routes parts to one `URDecoder` per message, so that several animated URs can be scanned at
+/// once. Messages are keyed by type, sequence length and checksum. Completed decoders are queued
+/// until the host takes them, and their remaining parts are ignored until the message has been
+/// idle as long as an incomplete one would be kept. At most `max_pending` incomplete messages are
+/// scanned at once, the one seen least recently makes room for a new one. Once `MAX_COMPLETED`
+/// messages wait to be taken, parts of other messages are refused until the host takes one.
+pub struct URDecoderSession {
+    idle_timeout: Option<Duration>,
+    max_pending: usize,
+    limits: DecoderLimits,
+    pending: HashMap<Message, Pending>,
+    finished: HashMap<Message, Instant>,
+    completed: VecDeque<URDecoder>,
+}
+
+impl URDecoderSession {
+    fn new(idle_timeout_ms: u32, max_pending: u32, limits: DecoderLimits) -> Self {
+        URDecoderSession {
+            idle_timeout: match idle_timeout_ms {
+                0 => None,
+                ms => Some(Duration::from_millis(ms as u64)),
+            },
+            max_pending: match max_pending {
+                0 => DEFAULT_MAX_PENDING as usize,
+                v => v as usize,
+            },
+            limits,
+            pending: HashMap::new(),
+            finished: HashMap::new(),
+            completed: VecDeque::new(),
+        }
+    }
+
+    /// Drops incomplete messages that did not receive a part within the idle timeout and
+    /// returns how many were dropped.
+    fn expire(&mut self, now: Instant) -> u32 {
+        let timeout = match self.idle_timeout {
+            Some(v) => v,
+            None => return 0,
+        };
+        let idle = |last_seen: Instant| now.saturating_duration_since(last_seen) > timeout;
+        let before = self.pending.len();
+        self.pending.retain(|_, pending| !idle(pending.last_seen));
+        self.finished.retain(|_, last_seen| !idle(*last_seen));
+        (before - self.pending.len()) as u32
+    }
+
+    /// Returns the type of the message the part belongs to, whether this part completed it and
+    /// how many incomplete messages were evicted to make room for it.
+    fn receive(&mut self, ur: &str, now: Instant) -> Result<(String, bool, u32), Error> {
+        let (seq, message) = parse_message(ur)?;
+        let ur_type = match &message {
+            Message::SinglePart { ur_type, .. } | Message::MultiPart { ur_type, .. } => ur_type.clone(),
+        };
+        if let Some(last_seen) = self.finished.get_mut(&message) {
+            *last_seen = now;
+            return Ok((ur_type, false, 0));
+        }
+        if self.completed.len() >= MAX_COMPLETED {
+            return Err(Error::new(
+                ErrorKind::LimitExceeded,
+                format!("{} completed messages have not been taken yet", MAX_COMPLETED),
+            ));
+        }
+        let mut pending = match self.pending.remove(&message) {
+            Some(v) => v,
+            None => Pending {
+                decoder: URDecoder::with_limits(self.limits),
+                last_seen: now,
+            },
+        };
+        let result = pending.decoder.receive_part(ur, seq, message.clone());
+        pending.last_seen = now;
+        if pending.decoder.is_complete() {
+            if self.finished.len() >= MAX_FINISHED {
+                evict_oldest(&mut self.finished, |last_seen| *last_seen);
+            }
+            self.finished.insert(message, now);
+            self.completed.push_back(pending.decoder);
+            return result.map(|_| (ur_type, true, 0));
+        }
+        let mut evicted = 0;
+        if pending.decoder.ur_type().is_some() {
+            if self.pending.len() >= self.max_pending {
+                evict_oldest(&mut self.pending, |pending| pending.last_seen);
+                evicted = 1;
+            }
+            self.pending.insert(message, pending);
+        }
+        result.map(|_| (ur_type, false, evicted))
+    }
+
+    fn pending_summary(&self, now: Instant) -> serde_json::Value {
+        let mut pending: Vec<&Pending> = self.pending.values().collect();
+        pending.sort_by_key(|p| std::cmp::Reverse(p.last_seen));
+        pending
+            .iter()
+            .map(|p| {
+                json!({
+                    "type": p.decoder.ur_type(),
+                    "expectedPartCount": p.decoder.expected_part_count(),
+                    "receivedPartsCount": p.decoder.received_parts_count(),
+                    "estimatedPercentComplete": p.decoder.estimated_percent_complete(),
+                    "idleMs": now.saturating_duration_since(p.last_seen).as_millis() as u64,
+                })
+            })
+            .collect()
+    }
+}
+
+impl RegistryObject for URDecoderSession {
+    fn object_type() -> &'static str {
+        "ur-decoder-session"
+    }
+}
+
+/// Creates a session that drops incomplete messages after `idle_timeout_ms` without a new part,
+/// `0` keeps them until the session is reset or they are evicted for a new message.
+#[no_mangle]
+pub extern "C" fn ur_decoder_session_new(idle_timeout_ms: u32) -> PtrResponse {
+    catch_panic(|| {
+        Response::success_object(URDecoderSession::new(idle_timeout_ms, 0, DecoderLimits::default())).c_ptr()
+    })
+}
+
+/// Like `ur_decoder_session_new`, scanning at most `max_pending` messages at once and decoding
+/// each one with the limits of `ur_decoder_new_with_limits`. `0` selects the default for that
+/// limit.
+#[no_mangle]
+pub extern "C" fn ur_decoder_session_new_with_limits(
+    idle_timeout_ms: u32,
+    max_pending: u32,
+    max_message_len: u32,
+    max_fragment_count: u32,
+    max_mixed_parts: u32,
+) -> PtrResponse {
+    catch_panic(|| {
+        let limits = DecoderLimits::new(max_message_len, max_fragment_count, max_mixed_parts);
+        Response::success_object(URDecoderSession::new(idle_timeout_ms, max_pending, limits)).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_session_free(session: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<URDecoderSession>(session).c_ptr()
+    })
+}
+
+/// Returns `{"type": "crypto-psbt", "complete": true, "expired": 0}`, where `complete` is only
+/// set by the part that completed its message and `expired` counts the incomplete messages
+/// dropped for being idle or to make room for a new one. Parts of new messages fail with
+/// `LimitExceeded` while too many completed messages wait for `ur_decoder_session_take_completed`.
+#[no_mangle]
+pub extern "C" fn ur_decoder_session_receive(session: PtrVoid, ur: PtrString) -> PtrResponse {
+    catch_panic(|| {
+        let ur_str = match convert_ptr_string_to_string(ur) {
+            Ok(value) => value.to_lowercase(),
+            Err(error) => return Response::error(error.for_field("ur")).c_ptr(),
+        };
+        with_object(session, |session: &mut URDecoderSession| {
+            let now = Instant::now();
+            let expired = session.expire(now);
+            match session.receive(&ur_str, now) {
+                Ok((ur_type, complete, evicted)) => Response::success_string(
+                    json!({"type": ur_type, "complete": complete, "expired": expired + evicted}).to_string(),
+                ),
+                Err(error) => Response::error(error.for_field("ur")),
+            }
+        })
+        .c_ptr()
+    })
+}
+
+/// Lists the messages still being scanned, most recently seen first.
+#[no_mangle]
+pub extern "C" fn ur_decoder_session_pending(session: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(session, |session: &mut URDecoderSession| {
+            let now = Instant::now();
+            session.expire(now);
+            Response::success_string(session.pending_summary(now).to_string())
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_decoder_session_completed_count(session: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(session, |session: &mut URDecoderSession| {
+            Response::success_uint32(session.completed.len() as u32)
+        })
+        .c_ptr()
+    })
+}
+
+/// Hands the oldest completed message over as a `ur-decoder`, to be used with `ur_decoder_resolve`
+/// and the other result getters and released with `ur_decoder_free`.
+#[no_mangle]
+pub extern "C" fn ur_decoder_session_take_completed(session: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(session, |session: &mut URDecoderSession| match session.completed.pop_front() {
+            Some(decoder) => Response::success_object(decoder),
+            None => Response::error(Error::new(ErrorKind::NotFound, "No completed message")),
+        })
+        .c_ptr()
+    })
+}
+
+/// Drops every pending and completed message, keeping the idle timeout and limits.
+#[no_mangle]
+pub extern "C" fn ur_decoder_session_reset(session: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(session, |session: &mut URDecoderSession| {
+            session.pending.clear();
+            session.finished.clear();
+            session.completed.clear();
+            Response::success_null()
+        })
+        .c_ptr()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ur_decoder::get_result;
+    use crate::ur_encoder::{new_encoder, EncoderOptions};
+
+    fn parts(message: &[u8], count: usize) -> Vec<String> {
+        let mut encoder = new_encoder(message, "bytes".to_string(), EncoderOptions::new(100, 0, 0)).unwrap();
+        (0..count).map(|_| encoder.next_part().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_interleaved_messages() {
+        let start = Instant::now();
+        let mut session = URDecoderSession::new(0, 0, DecoderLimits::default());
+        let first = parts(&[1; 500], 30);
+        let second = parts(&[2; 300], 30);
+        let mut completed = vec![];
+        for (a, b) in first.iter().zip(second.iter()) {
+            for part in [a, b] {
+                if session.receive(part, start).unwrap().1 {
+                    completed.push(session.completed.pop_front().unwrap());
+                }
+            }
+        }
+        assert_eq!(completed.len(), 2);
+        let mut messages: Vec<Vec<u8>> = completed.iter_mut().map(|d| get_result(d).unwrap()).collect();
+        messages.sort();
+        assert_eq!(messages, vec![vec![1; 500], vec![2; 300]]);
+        assert!(session.pending.is_empty());
+    }
+
+    #[test]
+    fn test_idle_messages_expire() {
+        let start = Instant::now();
+        let mut session = URDecoderSession::new(1000, 0, DecoderLimits::default());
+        let first = parts(&[1; 500], 2);
+        let second = parts(&[2; 500], 1);
+        session.receive(&first[0], start).unwrap();
+        session.receive(&second[0], start + Duration::from_millis(800)).unwrap();
+        assert_eq!(session.expire(start + Duration::from_millis(1500)), 1);
+        assert_eq!(session.pending.len(), 1);
+        session.receive(&first[1], start + Duration::from_millis(1500)).unwrap();
+        assert_eq!(session.pending_summary(start + Duration::from_millis(1500))[0]["receivedPartsCount"], 1);
+        assert_eq!(session.expire(start + Duration::from_millis(5000)), 2);
+    }
+
+    #[test]
+    fn test_pending_messages_are_bounded() {
+        let start = Instant::now();
+        let mut session = URDecoderSession::new(0, 2, DecoderLimits::default());
+        let messages: Vec<Vec<String>> = (1..=3).map(|i| parts(&[i; 500], 1)).collect();
+        for (i, message) in messages.iter().enumerate() {
+            let evicted = session.receive(&message[0], start + Duration::from_millis(i as u64)).unwrap().2;
+            assert_eq!(evicted, if i == 2 { 1 } else { 0 });
+        }
+        assert_eq!(session.pending.len(), 2);
+        // the first message was seen least recently and made room for the third
+        let (_, first) = parse_message(&messages[0][0]).unwrap();
+        assert!(!session.pending.contains_key(&first));
+
+        for i in 0..MAX_FINISHED as u8 + 4 {
+            let part = new_encoder(&[i], "bytes".to_string(), EncoderOptions::new(100, 0, 0)).unwrap().next_part().unwrap();
+            assert!(session.receive(&part, start).unwrap().1);
+            session.completed.pop_front().unwrap();
+        }
+        assert_eq!(session.finished.len(), MAX_FINISHED);
+    }
+
+    #[test]
+    fn test_completed_messages_are_bounded() {
+        let start = Instant::now();
+        let mut session = URDecoderSession::new(0, 0, DecoderLimits::default());
+        let part = |i: u8| parts(&[i], 1).remove(0);
+        for i in 0..MAX_COMPLETED as u8 {
+            assert!(session.receive(&part(i), start).unwrap().1);
+        }
+        let error = session.receive(&part(0xff), start).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+        // parts of messages that were already completed are still ignored
+        assert!(!session.receive(&part(0), start).unwrap().1);
+        assert_eq!(session.completed.len(), MAX_COMPLETED);
+
+        session.completed.pop_front().unwrap();
+        assert!(session.receive(&part(0xff), start).unwrap().1);
+    }
+
+    #[test]
+    fn test_session_limits_reach_decoders() {
+        let limits = DecoderLimits::new(100, 0, 0);
+        let mut session = URDecoderSession::new(0, 0, limits);
+        let error = session.receive(&parts(&[1; 500], 1)[0], Instant::now()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+        assert!(session.pending.is_empty());
+    }
+}
@@ -1,15 +1,357 @@
-use crate::response::{PtrResponse, Response};
+use crate::error::{Error, ErrorKind};
+use crate::handle::{free, with_object, RegistryObject};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::{PtrBytes, PtrString, PtrVoid};
+use crate::ur_part::{encode_payload, is_valid_type};
+use crate::utils::{convert_ptr_string_to_string, parse_ptr_bytes};
 use ur::Encoder;
 
+pub const DEFAULT_MAX_FRAGMENT_LEN: u32 = 400;
+pub const DEFAULT_MIN_FRAGMENT_LEN: u32 = 10;
+pub const MAX_FRAGMENT_LEN: u32 = 2048;
+pub const MAX_FIRST_SEQ_NUM: u32 = 100_000;
+
+/// How a message is split into parts. `min_fragment_len` and `first_seq_num` fall back to their
+/// defaults when `0`, the default min fragment length being capped by `max_fragment_len`.
+#[derive(Clone, Copy, Debug)]
+pub struct EncoderOptions {
+    pub max_fragment_len: u32,
+    pub min_fragment_len: u32,
+    pub first_seq_num: u32,
+}
+
+impl EncoderOptions {
+    pub fn new(max_fragment_len: u32, min_fragment_len: u32, first_seq_num: u32) -> Self {
+        EncoderOptions {
+            max_fragment_len,
+            min_fragment_len: match min_fragment_len {
+                0 => DEFAULT_MIN_FRAGMENT_LEN.min(max_fragment_len),
+                v => v,
+            },
+            first_seq_num: first_seq_num.max(1),
+        }
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if !(1..=MAX_FRAGMENT_LEN).contains(&self.max_fragment_len) || self.min_fragment_len == 0 {
+            return Err(Error::invalid_argument(
+                "max_fragment_len",
+                format!("max fragment length must be between 1 and {}", MAX_FRAGMENT_LEN),
+            ));
+        }
+        if self.min_fragment_len > self.max_fragment_len {
+            return Err(Error::invalid_argument(
+                "min_fragment_len",
+                "min fragment length must not exceed max fragment length",
+            ));
+        }
+        if self.first_seq_num > MAX_FIRST_SEQ_NUM {
+            return Err(Error::invalid_argument(
+                "first_seq_num",
+                format!("first sequence number must not exceed {}", MAX_FIRST_SEQ_NUM),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for EncoderOptions {
+    fn default() -> Self {
+        EncoderOptions::new(DEFAULT_MAX_FRAGMENT_LEN, 0, 0)
+    }
+}
+
+/// Picks the fragment length the same way the reference implementation does: the fewest
+/// fragments that fit `max_fragment_len`, without going below `min_fragment_len` unless it exceeds
+/// `max_fragment_len` itself.
+fn nominal_fragment_len(message_len: usize, min_fragment_len: usize, max_fragment_len: usize) -> usize {
+    let min_fragment_len = min_fragment_len.min(max_fragment_len);
+    let max_fragment_count = (message_len / min_fragment_len).max(1);
+    let mut fragment_len = max_fragment_len;
+    for fragment_count in 1..=max_fragment_count {
+        fragment_len = message_len.div_ceil(fragment_count);
+        if fragment_len <= max_fragment_len {
+            break;
+        }
+    }
+    fragment_len
+}
+
+/// Keeps the message around next to `ur::Encoder`, which neither exposes its sequence number
+/// nor can be rewound, so that the pure-sequence mode can start over from the first part.
+pub struct UREncoder {
+    encoder: Encoder,
+    message: Vec<u8>,
+    fragment_len: usize,
+    ur_type: String,
+    seq_num: u32,
+    pure_sequence: bool,
+}
+
+impl RegistryObject for UREncoder {
+    fn object_type() -> &'static str {
+        "ur-encoder"
+    }
+}
+
+impl UREncoder {
+    fn new(message: &[u8], fragment_len: usize, ur_type: String) -> Result<Self, Error> {
+        let encoder = Encoder::new(message, fragment_len, ur_type.clone())
+            .map_err(|e| Error::new(ErrorKind::Internal, e))?;
+        Ok(UREncoder {
+            encoder,
+            message: message.to_vec(),
+            fragment_len,
+            ur_type,
+            seq_num: 0,
+            pure_sequence: false,
+        })
+    }
+
+    pub fn fragment_count(&self) -> usize {
+        self.encoder.fragment_count()
+    }
+
+    pub fn is_single_part(&self) -> bool {
+        self.fragment_count() == 1
+    }
+
+    /// The sequence number of the last part returned by `next_part`, `0` before the first one.
+    pub fn seq_num(&self) -> u32 {
+        self.seq_num
+    }
+
+    pub fn next_part(&mut self) -> Result<String, Error> {
+        if self.is_single_part() {
+            self.seq_num = 1;
+            return self
+                .encoder
+                .get_single_part()
+                .map_err(|e| Error::new(ErrorKind::Internal, e));
+        }
+        if self.pure_sequence && self.seq_num as usize >= self.fragment_count() {
+            self.encoder = Encoder::new(&self.message, self.fragment_len, self.ur_type.clone())
+                .map_err(|e| Error::new(ErrorKind::Internal, e))?;
+            self.seq_num = 0;
+        }
+        let part = self
+            .encoder
+            .next_part()
+            .map_err(|e| Error::new(ErrorKind::Internal, e))?;
+        self.seq_num += 1;
+        Ok(part)
+    }
+
+    /// Goes back to an earlier `seq_num` by replaying the parts before it on a new `ur::Encoder`.
+    pub fn rewind(&mut self, seq_num: u32) -> Result<(), Error> {
+        let mut encoder = Encoder::new(&self.message, self.fragment_len, self.ur_type.clone())
+            .map_err(|e| Error::new(ErrorKind::Internal, e))?;
+        if !self.is_single_part() {
+            for _ in 0..seq_num {
+                encoder.next_part().map_err(|e| Error::new(ErrorKind::Internal, e))?;
+            }
+        }
+        self.encoder = encoder;
+        self.seq_num = seq_num;
+        Ok(())
+    }
+}
+
+pub fn new_encoder(message: &[u8], ur_type: String, options: EncoderOptions) -> Result<UREncoder, Error> {
+    options.validate()?;
+    let fragment_len = nominal_fragment_len(
+        message.len(),
+        options.min_fragment_len as usize,
+        options.max_fragment_len as usize,
+    );
+    let mut encoder = UREncoder::new(message, fragment_len, ur_type)?;
+    if !encoder.is_single_part() {
+        for _ in 1..options.first_seq_num {
+            encoder.next_part()?;
+        }
+    }
+    Ok(encoder)
+}
+
+fn parse_type_and_cbor(ur_type: PtrString, cbor: PtrBytes, cbor_len: usize) -> Result<(String, Vec<u8>), Error> {
+    let ur_type = convert_ptr_string_to_string(ur_type).map_err(|e| e.for_field("ur_type"))?;
+    if !is_valid_type(&ur_type) {
+        return Err(Error::invalid_argument(
+            "ur_type",
+            format!("UR type {} is invalid", ur_type),
+        ));
+    }
+    let cbor = parse_ptr_bytes(cbor, cbor_len).map_err(|e| e.for_field("cbor"))?;
+    if let Err(e) = serde_cbor::from_slice::<serde_cbor::Value>(&cbor) {
+        return Err(Error::new(ErrorKind::CborError, e).for_field("cbor"));
+    }
+    Ok((ur_type, cbor))
+}
+
+/// Wraps CBOR that has already been serialized by the host, so that UR types without a
+/// dedicated wrapper can be encoded as well.
+#[no_mangle]
+pub extern "C" fn ur_encoder_new(
+    ur_type: PtrString,
+    cbor: PtrBytes,
+    cbor_len: usize,
+    max_fragment_len: u32,
+) -> PtrResponse {
+    catch_panic(|| {
+        let (ur_type, cbor) = match parse_type_and_cbor(ur_type, cbor, cbor_len) {
+            Ok(v) => v,
+            Err(e) => return Response::error(e).c_ptr(),
+        };
+        match new_encoder(&cbor, ur_type, EncoderOptions::new(max_fragment_len, 0, 0)) {
+            Ok(ur_encoder) => Response::success_object(ur_encoder),
+            Err(e) => Response::error(e),
+        }
+        .c_ptr()
+    })
+}
+
+/// Encodes a whole message as a single-part `ur:<type>/<payload>` string, without an encoder.
+#[no_mangle]
+pub extern "C" fn ur_encode_single(ur_type: PtrString, cbor: PtrBytes, cbor_len: usize) -> PtrResponse {
+    catch_panic(|| {
+        match parse_type_and_cbor(ur_type, cbor, cbor_len) {
+            Ok((ur_type, cbor)) => Response::success_string(format!("ur:{}/{}", ur_type, encode_payload(&cbor))),
+            Err(e) => Response::error(e),
+        }
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_encoder_free(ur_encoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        free::<UREncoder>(ur_encoder).c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_encoder_next_part(ur_encoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(ur_encoder, |ur_encoder: &mut UREncoder| match ur_encoder.next_part() {
+            Ok(v) => Response::success_string(v),
+            Err(e) => Response::error(e),
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_encoder_fragment_count(ur_encoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(ur_encoder, |ur_encoder: &mut UREncoder| {
+            Response::success_uint32(ur_encoder.fragment_count() as u32)
+        })
+        .c_ptr()
+    })
+}
+
 #[no_mangle]
-pub extern "C" fn ur_encoder_next_part(ur_encoder: &mut Encoder) -> PtrResponse {
-    if ur_encoder.fragment_count() == 1 {
-        return Response::success_string(ur_encoder.get_single_part().unwrap()).c_ptr();
+pub extern "C" fn ur_encoder_current_sequence(ur_encoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(ur_encoder, |ur_encoder: &mut UREncoder| {
+            Response::success_uint32(ur_encoder.seq_num())
+        })
+        .c_ptr()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn ur_encoder_is_single_part(ur_encoder: PtrVoid) -> PtrResponse {
+    catch_panic(|| {
+        with_object(ur_encoder, |ur_encoder: &mut UREncoder| {
+            Response::success_boolean(ur_encoder.is_single_part())
+        })
+        .c_ptr()
+    })
+}
+
+/// In pure-sequence mode the encoder cycles through the first `fragment_count` parts, which
+/// carry one fragment each, and never emits fountain mixed parts.
+#[no_mangle]
+pub extern "C" fn ur_encoder_set_pure_sequence(ur_encoder: PtrVoid, pure_sequence: bool) -> PtrResponse {
+    catch_panic(|| {
+        with_object(ur_encoder, |ur_encoder: &mut UREncoder| {
+            ur_encoder.pure_sequence = pure_sequence;
+            Response::success_null()
+        })
+        .c_ptr()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nominal_fragment_len() {
+        assert_eq!(nominal_fragment_len(1000, 10, 400), 334);
+        assert_eq!(nominal_fragment_len(100, 10, 400), 100);
+        assert_eq!(nominal_fragment_len(25, 10, 5), 5);
+        assert_eq!(nominal_fragment_len(25, 10, 20), 13);
     }
 
-    match ur_encoder.next_part() {
-        Ok(v) => Response::success_string(v),
-        Err(e) => Response::error(e.to_string()),
+    #[test]
+    fn test_ur_encoder_new() {
+        let ur_type = || crate::utils::str_to_ptr_c_char("custom-type".to_string());
+        let cbor = serde_cbor::to_vec(&vec![1u32, 2, 3]).unwrap();
+        let response = Response::from_ptr(ur_encoder_new(ur_type(), cbor.as_ptr(), cbor.len(), 400));
+        assert_eq!(response.status_code, crate::response::SUCCESS);
+
+        let kind = |ur_type: &str, cbor: &[u8], max_fragment_len: u32| {
+            let ur_type = crate::utils::str_to_ptr_c_char(ur_type.to_string());
+            Response::from_ptr(ur_encoder_new(ur_type, cbor.as_ptr(), cbor.len(), max_fragment_len)).status_code
+        };
+        assert_eq!(kind("Custom_Type", &cbor, 400), ErrorKind::InvalidArgument as u32);
+        assert_eq!(kind("custom-type", &[0xff, 0xff], 400), ErrorKind::CborError as u32);
+        assert_eq!(kind("custom-type", &cbor, 0), ErrorKind::InvalidArgument as u32);
+    }
+
+    #[test]
+    fn test_pure_sequence() {
+        let message = vec![0u8; 1000];
+        let mut encoder = new_encoder(&message, "bytes".to_string(), EncoderOptions::new(100, 0, 0)).unwrap();
+        let fragment_count = encoder.fragment_count() as u32;
+        encoder.pure_sequence = true;
+        for round in 0..2 {
+            for seq in 1..=fragment_count {
+                let part = encoder.next_part().unwrap();
+                assert!(part.starts_with(&format!("ur:bytes/{}-{}/", seq, fragment_count)), "round {}", round);
+                assert_eq!(encoder.seq_num(), seq);
+            }
+        }
+        encoder.pure_sequence = false;
+        assert!(encoder.next_part().unwrap().starts_with(&format!("ur:bytes/{}-", fragment_count + 1)));
+        let next = encoder.next_part().unwrap();
+        encoder.rewind(fragment_count + 1).unwrap();
+        assert_eq!(encoder.seq_num(), fragment_count + 1);
+        assert_eq!(encoder.next_part().unwrap(), next);
+
+        let mut encoder = new_encoder(&message[..20], "bytes".to_string(), EncoderOptions::default()).unwrap();
+        assert!(encoder.is_single_part());
+        assert_eq!(encoder.seq_num(), 0);
+        assert!(encoder.next_part().unwrap().starts_with("ur:bytes/"));
+        assert_eq!(encoder.seq_num(), 1);
+    }
+
+    #[test]
+    fn test_new_encoder() {
+        let message = vec![0u8; 1000];
+        let encoder = new_encoder(&message, "bytes".to_string(), EncoderOptions::default()).unwrap();
+        assert_eq!(encoder.fragment_count(), 3);
+
+        let mut encoder = new_encoder(&message, "bytes".to_string(), EncoderOptions::new(100, 0, 5)).unwrap();
+        assert!(encoder.next_part().unwrap().starts_with("ur:bytes/5-"));
+        assert!(new_encoder(&message, "bytes".to_string(), EncoderOptions::new(5, 0, 0)).is_ok());
+
+        let kind = |options| new_encoder(&message, "bytes".to_string(), options).err().map(|e| e.kind());
+        assert_eq!(kind(EncoderOptions::new(0, 0, 0)), Some(ErrorKind::InvalidArgument));
+        assert_eq!(kind(EncoderOptions::new(MAX_FRAGMENT_LEN + 1, 0, 0)), Some(ErrorKind::InvalidArgument));
+        assert_eq!(kind(EncoderOptions::new(100, 200, 0)), Some(ErrorKind::InvalidArgument));
+        assert_eq!(kind(EncoderOptions::new(100, 0, MAX_FIRST_SEQ_NUM + 1)), Some(ErrorKind::InvalidArgument));
     }
-    .c_ptr()
 }
@@ -0,0 +1,244 @@
+use crate::error::{Error, ErrorKind};
+use crate::response::{catch_panic, PtrResponse, Response};
+use crate::types::PtrString;
+use crate::utils::convert_ptr_string_to_string;
+use serde_cbor::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use ur::bytewords::{self, Style};
+
+pub struct Header<'a> {
+    pub scheme: &'a str,
+    pub ur_type: &'a str,
+    pub sequence: Option<(u32, u32)>,
+    pub payload: &'a str,
+}
+
+/// Parses `ur:<type>/<payload>` and `ur:<type>/<seq>-<seq_len>/<payload>`, the UR is expected
+/// to be lowercase.
+pub fn parse_header(ur: &str) -> Result<Header<'_>, Error> {
+    let (scheme, rest) = match ur.split_once(':') {
+        Some(("ur", rest)) => ("ur", rest),
+        _ => return Err(Error::new(ErrorKind::InvalidScheme, "UR must start with ur:")),
+    };
+    let (ur_type, rest) = rest
+        .split_once('/')
+        .ok_or_else(|| Error::new(ErrorKind::InvalidScheme, "UR type is missing"))?;
+    if !is_valid_type(ur_type) {
+        return Err(Error::new(ErrorKind::InvalidScheme, format!("UR type {} is invalid", ur_type)));
+    }
+    let (sequence, payload) = match rest.split_once('/') {
+        Some((sequence, payload)) => (Some(parse_sequence(sequence)?), payload),
+        None => (None, rest),
+    };
+    Ok(Header {
+        scheme,
+        ur_type,
+        sequence,
+        payload,
+    })
+}
+
+/// UR types consist of lowercase letters, digits and hyphens.
+pub fn is_valid_type(ur_type: &str) -> bool {
+    !ur_type.is_empty()
+        && ur_type
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn parse_sequence(sequence: &str) -> Result<(u32, u32), Error> {
+    let invalid = || Error::new(ErrorKind::InvalidSequence, format!("sequence {} is invalid", sequence));
+    let (seq, seq_len) = sequence.split_once('-').ok_or_else(invalid)?;
+    let seq = seq.parse::<u32>().map_err(|_| invalid())?;
+    let seq_len = seq_len.parse::<u32>().map_err(|_| invalid())?;
+    if seq == 0 || seq_len == 0 {
+        return Err(invalid());
+    }
+    Ok((seq, seq_len))
+}
+
+pub fn encode_payload(data: &[u8]) -> String {
+    bytewords::encode(data, Style::Minimal)
+}
+
+/// Decodes the minimal bytewords payload, which also verifies its CRC32 checksum.
+pub fn decode_payload(payload: &str) -> Result<Vec<u8>, Error> {
+    bytewords::decode(payload, Style::Minimal).map_err(|e| Error::new(ErrorKind::InvalidBytewords, e))
+}
+
+/// Maps the two letter minimal form of every byteword to its byte, taken from the encoder so
+/// both directions share one word list.
+fn minimal_words() -> &'static HashMap<[u8; 2], u8> {
+    static WORDS: OnceLock<HashMap<[u8; 2], u8>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        (0..=u8::MAX)
+            .map(|byte| {
+                let word = bytewords::encode(&[byte], Style::Minimal);
+                ([word.as_bytes()[0], word.as_bytes()[1]], byte)
+            })
+            .collect()
+    })
+}
+
+/// Splits a minimal bytewords payload into its body and the trailing big endian CRC32 without
+/// verifying it. Unknown words and payloads too short to hold a checksum are `InvalidBytewords`.
+pub fn split_payload(payload: &str) -> Result<(Vec<u8>, u32), Error> {
+    let invalid = |message: &str| Error::new(ErrorKind::InvalidBytewords, message);
+    if !payload.is_ascii() || !payload.len().is_multiple_of(2) {
+        return Err(invalid("bytewords length is invalid"));
+    }
+    let mut data = payload
+        .as_bytes()
+        .chunks(2)
+        .map(|word| minimal_words().get(word).copied().ok_or_else(|| invalid("bytewords contain an invalid word")))
+        .collect::<Result<Vec<u8>, Error>>()?;
+    if data.len() < 4 {
+        return Err(invalid("bytewords are too short to contain a checksum"));
+    }
+    let checksum = data.split_off(data.len() - 4);
+    Ok((data, u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]])))
+}
+
+/// The CRC32 (IEEE) used by bytewords checksums.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// The header of a fountain encoded part, `[seq, seq_len, message_len, checksum, fragment]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FountainPart {
+    pub seq: u32,
+    pub seq_len: u32,
+    pub message_len: u64,
+    pub checksum: u32,
+}
+
+pub fn parse_fountain_part(data: &[u8]) -> Result<FountainPart, Error> {
+    let invalid = || Error::new(ErrorKind::InvalidFragment, "fountain part is invalid");
+    let items = match serde_cbor::from_slice::<Value>(data)
+        .map_err(|e| Error::new(ErrorKind::InvalidFragment, e))?
+    {
+        Value::Array(items) if items.len() == 5 => items,
+        _ => return Err(invalid()),
+    };
+    let uint = |value: &Value| match value {
+        Value::Integer(v) => u64::try_from(*v).ok(),
+        _ => None,
+    };
+    if !matches!(items[4], Value::Bytes(_)) {
+        return Err(invalid());
+    }
+    Ok(FountainPart {
+        seq: uint(&items[0]).and_then(|v| u32::try_from(v).ok()).ok_or_else(invalid)?,
+        seq_len: uint(&items[1]).and_then(|v| u32::try_from(v).ok()).ok_or_else(invalid)?,
+        message_len: uint(&items[2]).ok_or_else(invalid)?,
+        checksum: uint(&items[3]).and_then(|v| u32::try_from(v).ok()).ok_or_else(invalid)?,
+    })
+}
+
+/// Describes a UR string without decoding it, e.g.
+/// `{"scheme": "ur", "type": "crypto-psbt", "multipart": true, "seq": 1, "seqLen": 3, "checksumValid": true}`.
+/// `seq` and `seqLen` are `1` for a single-part UR. Strings that are not URs at all are reported
+/// as `InvalidScheme` errors and malformed bytewords as `InvalidBytewords` errors, while
+/// `checksumValid` only reflects whether the trailing CRC32 matches the payload.
+#[no_mangle]
+pub extern "C" fn ur_parse(ur: PtrString) -> PtrResponse {
+    catch_panic(|| {
+        let ur_str = match convert_ptr_string_to_string(ur) {
+            Ok(value) => value.to_lowercase(),
+            Err(error) => return Response::error(error.for_field("ur")).c_ptr(),
+        };
+        let header = match parse_header(&ur_str) {
+            Ok(v) => v,
+            Err(error) => return Response::error(error.for_field("ur")).c_ptr(),
+        };
+        let (seq, seq_len) = header.sequence.unwrap_or((1, 1));
+        let (data, checksum) = match split_payload(header.payload) {
+            Ok(v) => v,
+            Err(error) => return Response::error(error.for_field("ur")).c_ptr(),
+        };
+        Response::success_string(
+            json!({
+                "scheme": header.scheme,
+                "type": header.ur_type,
+                "multipart": header.sequence.is_some(),
+                "seq": seq,
+                "seqLen": seq_len,
+                "checksumValid": crc32(&data) == checksum,
+            })
+            .to_string(),
+        )
+        .c_ptr()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        let header = parse_header("ur:crypto-psbt/12-3/lpadaxcs").unwrap();
+        assert_eq!(header.scheme, "ur");
+        assert_eq!(header.ur_type, "crypto-psbt");
+        assert_eq!(header.sequence, Some((12, 3)));
+        assert_eq!(header.payload, "lpadaxcs");
+        let header = parse_header("ur:eth-signature/hdcx").unwrap();
+        assert_eq!(header.ur_type, "eth-signature");
+        assert_eq!(header.sequence, None);
+
+        let kind = |ur: &str| parse_header(ur).err().map(|e| e.kind());
+        assert_eq!(kind("crypto-psbt/hdcx"), Some(ErrorKind::InvalidScheme));
+        assert_eq!(kind("https://example.com"), Some(ErrorKind::InvalidScheme));
+        assert_eq!(kind("ur:bytes"), Some(ErrorKind::InvalidScheme));
+        assert_eq!(kind("ur:by_tes/hdcx"), Some(ErrorKind::InvalidScheme));
+        assert_eq!(kind("ur:bytes/x-3/hdcx"), Some(ErrorKind::InvalidSequence));
+        assert_eq!(kind("ur:bytes/0-3/hdcx"), Some(ErrorKind::InvalidSequence));
+    }
+
+    #[test]
+    fn test_parse_fountain_part() {
+        let data = serde_cbor::to_vec(&(2u32, 3u32, 100u64, 0xdeadbeefu32, serde_cbor::Value::Bytes(vec![1, 2]))).unwrap();
+        assert_eq!(
+            parse_fountain_part(&data).unwrap(),
+            FountainPart {
+                seq: 2,
+                seq_len: 3,
+                message_len: 100,
+                checksum: 0xdeadbeef,
+            }
+        );
+        let data = serde_cbor::to_vec(&(2u32, 3u32)).unwrap();
+        assert_eq!(parse_fountain_part(&data).err().map(|e| e.kind()), Some(ErrorKind::InvalidFragment));
+        assert_eq!(decode_payload("zzzz").err().map(|e| e.kind()), Some(ErrorKind::InvalidBytewords));
+    }
+
+    #[test]
+    fn test_split_payload() {
+        let payload = encode_payload(&[0x83, 0x01, 0x02, 0x03]);
+        let (data, checksum) = split_payload(&payload).unwrap();
+        assert_eq!(data, vec![0x83, 0x01, 0x02, 0x03]);
+        assert_eq!(crc32(&data), checksum);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+
+        let other = if payload.ends_with("ae") { "ad" } else { "ae" };
+        let tampered = format!("{}{}", &payload[..payload.len() - 2], other);
+        let (data, checksum) = split_payload(&tampered).unwrap();
+        assert_ne!(crc32(&data), checksum);
+
+        let kind = |payload: &str| split_payload(payload).err().map(|e| e.kind());
+        assert_eq!(kind(&format!("{}a", payload)), Some(ErrorKind::InvalidBytewords));
+        assert_eq!(kind(&format!("zz{}", payload)), Some(ErrorKind::InvalidBytewords));
+        assert_eq!(kind("aeadae"), Some(ErrorKind::InvalidBytewords));
+        assert_eq!(kind("aéad"), Some(ErrorKind::InvalidBytewords));
+    }
+}
@@ -1,6 +1,8 @@
-use crate::types::PtrString;
+use crate::error::{Error, ErrorKind};
+use crate::handle::free_any;
+use crate::types::{PtrBytes, PtrString, PtrVoid};
 use hex::FromHex;
-use std::ffi::{c_void, CStr, CString};
+use std::ffi::{CStr, CString};
 
 pub fn remove_prefix<'a>(s: &'a str, prefix: &str) -> &'a str {
     match s.strip_prefix(prefix) {
@@ -14,33 +16,50 @@ pub fn remove_prefix_0x(s: &str) -> &str {
 }
 
 #[no_mangle]
-pub extern "C" fn utils_free(any_ptr: *mut c_void) {
-    if any_ptr.is_null() {
-        return;
-    }
-    unsafe {
-        Box::from_raw(any_ptr);
-    }
+pub extern "C" fn utils_free(any_ptr: PtrVoid) {
+    free_any(any_ptr);
 }
 
+/// Hands a string over as a C string, failing on interior NUL bytes rather than truncating the
+/// string at the first one.
+pub fn try_str_to_ptr_c_char(str: String) -> Result<PtrString, Error> {
+    CString::new(str).map(CString::into_raw).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidArgument,
+            format!("string contains a NUL byte at position {}", e.nul_position()),
+        )
+    })
+}
+
+/// For strings that cannot contain NUL bytes, such as type names and JSON, see
+/// `try_str_to_ptr_c_char` for any other string.
 pub fn str_to_ptr_c_char(str: String) -> PtrString {
-    CString::new(str).unwrap().into_raw()
+    try_str_to_ptr_c_char(str).expect("string must not contain NUL bytes")
 }
 
-pub fn parse_ptr_string_to_bytes(str: PtrString) -> Result<Vec<u8>, String> {
-    unsafe {
-        CStr::from_ptr(str)
-            .to_str()
-            .map_err(|e| e.to_string())
-            .and_then(|v| Vec::from_hex(v.to_string()).map_err(|e| e.to_string()))
+pub fn parse_ptr_string_to_bytes(str: PtrString) -> Result<Vec<u8>, Error> {
+    convert_ptr_string_to_string(str)
+        .and_then(|v| Vec::from_hex(v).map_err(|e| Error::new(ErrorKind::InvalidHex, e)))
+}
+
+pub fn parse_ptr_bytes(data: PtrBytes, len: usize) -> Result<Vec<u8>, Error> {
+    if len == 0 {
+        return Ok(vec![]);
+    }
+    if data.is_null() {
+        return Err(Error::new(ErrorKind::InvalidArgument, "null pointer"));
     }
+    unsafe { Ok(std::slice::from_raw_parts(data, len).to_vec()) }
 }
 
-pub fn convert_ptr_string_to_string(str: PtrString) -> Result<String, String> {
+pub fn convert_ptr_string_to_string(str: PtrString) -> Result<String, Error> {
+    if str.is_null() {
+        return Err(Error::new(ErrorKind::InvalidArgument, "null pointer"));
+    }
     unsafe {
         CStr::from_ptr(str)
             .to_str()
-            .map_err(|e| e.to_string())
+            .map_err(|e| Error::new(ErrorKind::InvalidArgument, e))
             .map(|v| v.to_string())
     }
 }